@@ -42,7 +42,23 @@ pub enum ServiceErrorType {
     SerializationError,
     ResourceNotFound,
     IOError,
-    Unavailable
+    Unavailable,
+    Timeout,
+}
+
+impl ServiceErrorType {
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            404 | 410 => ServiceErrorType::ResourceNotFound,
+            408 => ServiceErrorType::Timeout,
+            429 | 500..=599 => ServiceErrorType::Unavailable,
+            _ => ServiceErrorType::Failure,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ServiceErrorType::Unavailable | ServiceErrorType::Timeout)
+    }
 }
 #[derive(Debug, Serialize, PartialEq)]
 pub struct TestError {
@@ -116,11 +132,14 @@ impl From<ServiceError> for AppError {
 impl From<reqwest::Error> for DictionaryError {
     fn from(error: reqwest::Error) -> Self {
         let msg = format!(
-            "Request failed. Status: {}, error: {}",
-            error.status().unwrap(),
+            "Request failed. Status: {:?}, error: {}",
+            error.status(),
             error.to_string()
         );
 
+        if error.is_timeout() {
+            return DictionaryError::throw(&msg, DictionaryErrorKind::Timeout);
+        }
         DictionaryError::throw(&msg, DictionaryErrorKind::NotFound)
     }
 }
@@ -147,15 +166,24 @@ impl From<FromUtf8Error> for ServiceError {
 
 impl From<reqwest::Error> for ServiceError {
     fn from(error: reqwest::Error) -> Self {
+        let error_type = if error.is_timeout() {
+            ServiceErrorType::Timeout
+        } else if let Some(status) = error.status() {
+            ServiceErrorType::from_status(status.as_u16())
+        } else if error.is_connect() || error.is_request() || error.is_body() {
+            ServiceErrorType::Unavailable
+        } else {
+            ServiceErrorType::Failure
+        };
         let msg = format!(
-            "Request failed. Status: {}, error: {}",
-            error.status().unwrap(),
+            "Request failed. Status: {:?}, error: {}",
+            error.status(),
             error.to_string()
         );
 
         ServiceError{
             message: msg,
-            error_type: ServiceErrorType::ResourceNotFound,
+            error_type,
         }
     }
 }
@@ -31,6 +31,14 @@ pub fn find_file<T: AsRef<str>>(dir: T, search_pattern: T) -> Vec<String> {
         names
 }
 
+/// An empty `name` directory under the system temp dir, cleared first; used by the tests.
+pub fn temp_dir<T: AsRef<str>>(name: T) -> String {
+    let dir = std::env::temp_dir().join(name.as_ref());
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp.dir.not.created");
+    dir.to_string_lossy().to_string()
+}
+
 pub fn file_name<T: AsRef<str>>(folder: T, name: T, ext: &str) -> String {
     let mut file_name = String::from(folder.as_ref());
     if !file_name.ends_with("/") {
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+
+/// Adverbs and prepositions that make a phrasal verb.
+const PARTICLES: &[&str] = &[
+    "up", "down", "off", "out", "in", "on", "over", "away", "back", "through", "around", "about", "along", "apart",
+    "aside", "forward", "together",
+];
+
+/// Words between a verb and its particle, e.g. `call the meeting off`.
+const MAX_OBJECT_WORDS: usize = 2;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any", "are", "as", "at",
+    "be", "because", "been", "before", "being", "below", "between", "both", "but", "by", "can", "could", "did", "do",
+    "does", "doing", "done", "down", "during", "each", "even", "ever", "every", "few", "for", "from", "further", "get",
+    "had", "has", "have", "having", "he", "her", "here", "hers", "herself", "him", "himself", "his", "how", "however",
+    "i", "if", "in", "into", "is", "it", "its", "itself", "just", "last", "least", "less", "like", "many", "may", "me",
+    "might", "more", "most", "much", "must", "my", "myself", "new", "no", "nor", "not", "now", "of", "off", "often",
+    "on", "once", "one", "only", "or", "other", "our", "ours", "ourselves", "out", "over", "own", "per", "same",
+    "say", "she", "should", "so", "some", "still", "such", "than", "that", "the", "their", "theirs", "them",
+    "themselves", "then", "there", "these", "they", "this", "those", "though", "through", "to", "too", "two",
+    "under", "until", "up", "upon", "us", "very", "was", "we", "were", "what", "when", "where", "whether", "which",
+    "while", "who", "whom", "why", "will", "with", "would", "yet", "you", "your",
+];
+
+/// Irregular forms and their lemma.
+const IRREGULAR: &[(&str, &str)] = &[
+    ("am", "be"), ("are", "be"), ("is", "be"), ("was", "be"), ("were", "be"), ("been", "be"), ("has", "have"),
+    ("had", "have"), ("did", "do"), ("done", "do"), ("does", "do"), ("went", "go"), ("gone", "go"),
+    ("goes", "go"), ("came", "come"), ("became", "become"), ("ran", "run"), ("took", "take"), ("taken", "take"),
+    ("gave", "give"), ("given", "give"), ("got", "get"), ("gotten", "get"), ("made", "make"), ("brought", "bring"),
+    ("bought", "buy"), ("held", "hold"), ("broke", "break"), ("broken", "break"), ("wound", "wind"),
+    ("fought", "fight"), ("kept", "keep"), ("left", "leave"), ("led", "lead"), ("told", "tell"), ("said", "say"),
+    ("thought", "think"), ("saw", "see"), ("seen", "see"), ("knew", "know"), ("known", "know"), ("began", "begin"),
+    ("begun", "begin"), ("drew", "draw"), ("drawn", "draw"), ("fell", "fall"), ("fallen", "fall"), ("felt", "feel"),
+    ("found", "find"), ("stood", "stand"), ("understood", "understand"), ("sought", "seek"), ("caught", "catch"),
+    ("taught", "teach"), ("meant", "mean"), ("met", "meet"), ("paid", "pay"), ("laid", "lay"), ("lay", "lie"),
+    ("lain", "lie"), ("rose", "rise"), ("risen", "rise"), ("wrote", "write"), ("written", "write"),
+    ("spoke", "speak"), ("spoken", "speak"), ("chose", "choose"), ("chosen", "choose"), ("froze", "freeze"),
+    ("frozen", "freeze"), ("drove", "drive"), ("driven", "drive"), ("threw", "throw"), ("thrown", "throw"),
+    ("grew", "grow"), ("grown", "grow"), ("blew", "blow"), ("blown", "blow"), ("flew", "fly"), ("flown", "fly"),
+    ("shook", "shake"), ("shaken", "shake"), ("sold", "sell"), ("sent", "send"), ("spent", "spend"),
+    ("built", "build"), ("lost", "lose"), ("won", "win"), ("sat", "sit"), ("slept", "sleep"), ("struck", "strike"),
+    ("stuck", "stick"), ("clung", "cling"), ("swung", "swing"), ("hung", "hang"), ("dug", "dig"), ("fed", "feed"),
+    ("fled", "flee"), ("bred", "breed"), ("bound", "bind"), ("sank", "sink"), ("sunk", "sink"), ("swore", "swear"),
+    ("sworn", "swear"), ("tore", "tear"), ("torn", "tear"), ("wore", "wear"), ("worn", "wear"),
+    ("children", "child"), ("men", "man"), ("women", "woman"), ("people", "person"), ("feet", "foot"),
+    ("teeth", "tooth"), ("mice", "mouse"), ("crises", "crisis"),
+];
+
+/// Common phrasal verbs and expressions, on top of the ones the learner already has.
+const EXPRESSIONS: &[&str] = &[
+    "back down", "back off", "back up", "bail out", "beef up", "blow up", "break down", "break out", "break up",
+    "bring about", "bring up", "call off", "call on", "carry on", "carry out", "catch up", "clamp down",
+    "come about", "come across", "come up", "cut back", "cut off", "deal with", "dust up", "end up", "fall apart",
+    "fall through", "fend off", "figure out", "fill in", "find out", "get along", "get over", "give in", "give up",
+    "go ahead", "go on", "hand over", "hold back", "hold off", "hold on", "keep up", "lay off", "let down",
+    "look after", "look into", "make up", "pass away", "phase out", "pick up", "point out", "pull out",
+    "put off", "put up with", "rule out", "run out", "set off", "set out", "set up", "shut down", "sort out",
+    "speak out", "step down", "step up", "take off", "take over", "take up", "tear down", "turn down", "turn out",
+    "wind down", "wind up", "work out", "wipe out", "in spite of", "on behalf of", "as well as", "due course",
+    "fait accompli", "in the wake of",
+];
+
+/// The clauses of the text as lower case words; an expression never spans
+/// two of them. Apostrophes and hyphens stay inside words, numbers are dropped.
+pub fn tokenize(text: &str) -> Vec<Vec<String>> {
+    let text = text.replace(['’', '‘'], "'");
+    let mut sentences = vec![];
+    for sentence in text.split(|c: char| ".,!?;:()[]\"“”—–\n".contains(c)) {
+        let words: Vec<String> = sentence
+            .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '-'))
+            .map(|word| word.trim_matches(|c: char| c == '\'' || c == '-').to_lowercase())
+            .map(|word| word.strip_suffix("'s").map(str::to_string).unwrap_or(word))
+            .filter(|word| !word.is_empty() && !word.chars().any(|c| c.is_numeric()))
+            .collect();
+        if !words.is_empty() {
+            sentences.push(words);
+        }
+    }
+    sentences
+}
+
+/// The dictionary form of the word: `clings` -> `cling`, `came` -> `come`,
+/// `coerced` -> `coerce`. A form found in `vocabulary` wins over the rules.
+pub fn lemma(word: &str, vocabulary: &HashSet<String>) -> String {
+    if let Some((_, lemma)) = IRREGULAR.iter().find(|(form, _)| *form == word) {
+        return lemma.to_string();
+    }
+    if word.contains('\'') || word.chars().count() < 4 {
+        return word.to_string();
+    }
+
+    let mut candidates = vec![];
+    if let Some(stem) = word.strip_suffix("ies").or_else(|| word.strip_suffix("ied")) {
+        candidates.push(format!("{}y", stem));
+    } else if let Some(stem) = ["ing", "ed"].iter().find_map(|suffix| word.strip_suffix(suffix)) {
+        if stem.chars().count() >= 2 {
+            candidates.extend(verb_stems(stem));
+        }
+    } else if ["sses", "ches", "shes", "xes", "zes"].iter().any(|suffix| word.ends_with(suffix)) {
+        candidates.push(word[..word.len() - 2].to_string());
+    } else if word.ends_with('s') && !["ss", "us", "is"].iter().any(|suffix| word.ends_with(suffix)) {
+        candidates.push(word[..word.len() - 1].to_string());
+    }
+
+    match candidates.iter().find(|candidate| vocabulary.contains(*candidate)) {
+        Some(candidate) => candidate.clone(),
+        None if candidates.is_empty() || vocabulary.contains(word) => word.to_string(),
+        None => candidates[0].clone(),
+    }
+}
+
+/// What `-ed` or `-ing` was added to, the most likely first: `stopp` ->
+/// `stop`, `coerc` -> `coerce`, `mak` -> `make`, `cling` -> `cling`.
+fn verb_stems(stem: &str) -> Vec<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    let vowel = |c: char| "aeiou".contains(c);
+    let last = chars[chars.len() - 1];
+    let before = chars[chars.len() - 2];
+    let undoubled = chars[..chars.len() - 1].iter().collect::<String>();
+    let with_e = format!("{}e", stem);
+
+    if last == before && !vowel(last) && !"lsz".contains(last) {
+        return vec![undoubled, stem.to_string()];
+    }
+    let needs_e = "cvz".contains(last) && before != 'i'
+        || (last == 'l' && !vowel(before) && before != 'l')
+        || (last == 'g' && "dr".contains(before))
+        || (last == 's' && vowel(before) && before != 'u')
+        || (last == 'r' && before == 'u')
+        || stem.ends_with("at") && chars.len() > 3
+        || (chars.len() == 3 && !vowel(chars[0]) && vowel(before) && !vowel(last) && !"wxy".contains(last));
+    if needs_e {
+        vec![with_e, stem.to_string()]
+    } else {
+        vec![stem.to_string(), with_e]
+    }
+}
+
+/// Turns pasted text into new words to download: lemmas, phrasal verbs and
+/// expressions once each, in the order they appear, without stop-words and
+/// without the `known` words. `expressions` adds to the built-in ones.
+pub fn ingest(text: &str, known: &[String], expressions: &[String]) -> Vec<String> {
+    let sentences = tokenize(text);
+    let known: HashSet<String> = known.iter().map(|word| word.trim().to_lowercase()).collect();
+    // The plain forms of the text tell `coerced` is `coerce` when both are there.
+    let mut vocabulary: HashSet<String> = sentences
+        .iter()
+        .flatten()
+        .filter(|word| !["s", "ed", "ing"].iter().any(|suffix| word.ends_with(suffix)))
+        .cloned()
+        .collect();
+    vocabulary.extend(known.iter().cloned());
+    let stop_words: HashSet<&str> = STOP_WORDS.iter().cloned().collect();
+    let mut lexicon: Vec<Vec<String>> = EXPRESSIONS
+        .iter()
+        .map(|expression| expression.to_string())
+        .chain(expressions.iter().map(|expression| expression.trim().to_lowercase()))
+        .filter(|expression| expression.contains(' '))
+        .map(|expression| expression.split_whitespace().map(str::to_string).collect())
+        .collect();
+    lexicon.sort_by_key(|words: &Vec<String>| std::cmp::Reverse(words.len()));
+
+    let mut words: Vec<String> = vec![];
+    let mut add = |word: String| {
+        if !known.contains(&word) && !words.contains(&word) {
+            words.push(word);
+        }
+    };
+    for sentence in sentences {
+        let lemmas: Vec<String> = sentence.iter().map(|word| lemma(word, &vocabulary)).collect();
+        let mut used = vec![false; sentence.len()];
+        for i in 0..sentence.len() {
+            if used[i] {
+                continue;
+            }
+            if let Some((expression, positions)) = lexicon.iter().find_map(|expression| {
+                find_expression(&sentence, &lemmas, &used, i, expression).map(|positions| (expression, positions))
+            }) {
+                for position in positions {
+                    used[position] = true;
+                }
+                add(expression.join(" "));
+                continue;
+            }
+            let lemma = &lemmas[i];
+            let stop_word = stop_words.contains(lemma.as_str()) || stop_words.contains(sentence[i].as_str());
+            if lemma.chars().count() > 2 && !stop_word {
+                add(lemma.clone());
+            }
+        }
+    }
+    words
+}
+
+/// Positions of the expression starting at `start`, matching the words as
+/// written or their lemmas. A phrasal verb may have its object between the
+/// verb and the particle.
+fn find_expression(
+    words: &[String],
+    lemmas: &[String],
+    used: &[bool],
+    start: usize,
+    expression: &[String],
+) -> Option<Vec<usize>> {
+    let matches = |position: usize, word: &String| {
+        !used[position] && (words[position] == *word || lemmas[position] == *word)
+    };
+    if !matches(start, &expression[0]) {
+        return None;
+    }
+    let end = start + expression.len();
+    if end <= words.len() && (start..end).zip(expression).all(|(position, word)| matches(position, word)) {
+        return Some((start..end).collect());
+    }
+    if expression.len() == 2 && PARTICLES.contains(&expression[1].as_str()) {
+        let end = (start + 2 + MAX_OBJECT_WORDS).min(words.len());
+        return (start + 2..end)
+            .find(|position| matches(*position, &expression[1]))
+            .map(|position| vec![start, position]);
+    }
+    None
+}
+
+#[cfg(test)]
+mod ingest_tests {
+    use std::collections::HashSet;
+
+    use super::{ingest, lemma, tokenize};
+
+    #[test]
+    fn tokenize_test() {
+        assert_eq!(
+            vec![
+                vec!["the", "minister", "wound", "up", "the", "government"],
+                vec!["do", "it"],
+                vec!["not", "do", "it"],
+                vec!["well-known", "rust", "don't"],
+            ],
+            tokenize("The minister’s wound up the Government's 2023 — do it? Not do it!\nWell-known 'Rust' don't")
+        );
+        assert!(tokenize(" .. 42 !").is_empty());
+    }
+
+    #[test]
+    fn lemma_test() {
+        let vocabulary = HashSet::new();
+        for (word, expected) in [
+            ("clings", "cling"),
+            ("clinging", "cling"),
+            ("came", "come"),
+            ("coerced", "coerce"),
+            ("stopped", "stop"),
+            ("stifled", "stifle"),
+            ("deteriorating", "deteriorate"),
+            ("emerged", "emerge"),
+            ("making", "make"),
+            ("waned", "wane"),
+            ("soared", "soar"),
+            ("opened", "open"),
+            ("studies", "study"),
+            ("watches", "watch"),
+            ("stances", "stance"),
+            ("dismissed", "dismiss"),
+            ("ceased", "cease"),
+            ("endured", "endure"),
+            ("focus", "focus"),
+            ("crisis", "crisis"),
+            ("crises", "crisis"),
+            ("class", "class"),
+            ("allegations", "allegation"),
+            ("bolstering", "bolster"),
+            ("don't", "don't"),
+        ] {
+            assert_eq!(expected, lemma(word, &vocabulary), "{}", word);
+        }
+        let vocabulary: HashSet<String> = ["focuse".to_string()].into_iter().collect();
+        assert_eq!("focuse", lemma("focused", &vocabulary));
+        assert_eq!("focus", lemma("focused", &HashSet::new()));
+        let vocabulary: HashSet<String> = ["news".to_string()].into_iter().collect();
+        assert_eq!("news", lemma("news", &vocabulary));
+    }
+
+    #[test]
+    fn ingest_test() {
+        let text = "The talks came up short. Rebels clung to the city and the army called the assault off; \
+                    the minister wound up the session. In the wake of the vote, tension clings to the capital \
+                    as critics beef up their campaign. The stance is stifling.";
+        let known = vec!["Stance".to_string(), "tension".to_string()];
+        assert_eq!(
+            vec![
+                "talk", "come up", "short", "rebel", "cling", "city", "army", "call off", "assault", "minister",
+                "wind up", "session", "in the wake of", "vote", "capital", "critic", "beef up", "campaign", "stifle",
+            ],
+            ingest(text, &known, &[])
+        );
+        assert_eq!(
+            vec!["the ruling party", "lose", "ground"],
+            ingest("The ruling party lost ground", &[], &["The ruling party".to_string()])
+        );
+        assert!(ingest("It is what it is.", &[], &[]).is_empty());
+    }
+}
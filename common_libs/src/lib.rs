@@ -1,6 +1,11 @@
 pub mod utils;
 pub mod files;
+pub mod ingest;
 pub mod error;
+pub mod player;
+pub mod scheduler;
+pub mod scoring;
+pub mod stats;
 
 use log::warn;
 
@@ -0,0 +1,33 @@
+use std::{fs::File, io::BufReader, thread, time::Duration};
+
+use rodio::{Decoder, OutputStream, Sink};
+
+use crate::error::{ServiceError, ServiceErrorType, ServiceExuctionResult};
+
+/// Pause between two plays of the same file.
+pub const PAUSE_MILLIS: u64 = 700;
+
+/// Plays the file `times` times on the default output device and returns when it is done.
+pub fn play(file_name: &str, times: u32) -> ServiceExuctionResult<()> {
+    let (_stream, handle) = OutputStream::try_default().map_err(|err| ServiceError {
+        message: format!("audio.device.not.available: {}", err),
+        error_type: ServiceErrorType::Unavailable,
+    })?;
+    let sink = Sink::try_new(&handle).map_err(|err| ServiceError {
+        message: format!("audio.device.not.available: {}", err),
+        error_type: ServiceErrorType::Unavailable,
+    })?;
+
+    for played in 0..times {
+        let decoder = Decoder::new(BufReader::new(File::open(file_name)?)).map_err(|err| ServiceError {
+            message: format!("invalid.audio: {} -> {}", file_name, err),
+            error_type: ServiceErrorType::Failure,
+        })?;
+        sink.append(decoder);
+        sink.sleep_until_end();
+        if played + 1 < times {
+            thread::sleep(Duration::from_millis(PAUSE_MILLIS));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::stats::{Outcome, Statistic, WordStatistic};
+
+pub const INITIAL_EASE_FACTOR: f64 = 2.5;
+pub const MINIMUM_EASE_FACTOR: f64 = 1.3;
+/// An answer slower than this is not counted as effortless.
+pub const SLOW_ANSWER_SECONDS: u64 = 15;
+/// Default `--passing-score`; answers saved without their outcome are judged by it.
+pub const PASSING_SCORE: u8 = 85;
+
+/// SM-2 quality of an answer, 0 (blackout) to 5 (perfect). The outcome
+/// decides whether the answer counts as recalled: a passed answer is at
+/// least 3 and a failed or timed out one at most 2, whatever its score.
+pub fn quality(outcome: Outcome, score: u8, respond_time: u64) -> u8 {
+    let quality = match score {
+        100 => 5,
+        90..=99 => 4,
+        75..=89 => 3,
+        50..=74 => 2,
+        25..=49 => 1,
+        _ => 0,
+    };
+    let quality = if quality > 3 && respond_time > SLOW_ANSWER_SECONDS {
+        quality - 1
+    } else {
+        quality
+    };
+    match outcome {
+        Outcome::Success => quality.max(3),
+        Outcome::Error | Outcome::Timeout => quality.min(2),
+    }
+}
+
+/// When a word is asked in a session: due words first, the most overdue of
+/// them first, then words never answered, then the rest by review date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Due(DateTime<Utc>),
+    New,
+    Later(DateTime<Utc>),
+}
+
+impl WordStatistic {
+    /// Applies one answer of the given quality as of `reviewed_on`.
+    pub fn review(&mut self, quality: u8, reviewed_on: DateTime<Utc>) {
+        let quality = quality.min(5) as f64;
+        if quality >= 3.0 {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease_factor).round() as u32,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval = 1;
+        }
+        let ease_factor = self.ease_factor + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02);
+        self.ease_factor = (ease_factor.max(MINIMUM_EASE_FACTOR) * 100.0).round() / 100.0;
+        self.next_review = Some(reviewed_on + Duration::days(self.interval as i64));
+    }
+
+    /// Rebuilds the schedule from the recorded answers. Their dates and
+    /// outcomes are not kept, so the last one is taken as answered on
+    /// `updated_on` and each passed when it reaches `PASSING_SCORE`.
+    pub fn reschedule(&mut self) {
+        self.ease_factor = INITIAL_EASE_FACTOR;
+        self.interval = 0;
+        self.repetitions = 0;
+        self.next_review = None;
+        let answers: Vec<(u8, u64)> = self
+            .scores
+            .iter()
+            .cloned()
+            .zip(self.respond_times.iter().cloned().chain(std::iter::repeat(0)))
+            .collect();
+        for (score, respond_time) in answers {
+            let outcome = if score >= PASSING_SCORE { Outcome::Success } else { Outcome::Error };
+            self.review(quality(outcome, score, respond_time), self.updated_on);
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_review.is_none_or(|next_review| next_review <= now)
+    }
+}
+
+impl Statistic {
+    /// Schedules the records saved before the scheduler existed.
+    pub fn schedule_missing(&mut self) {
+        for record in self.records.iter_mut() {
+            if record.next_review.is_none() && !record.scores.is_empty() {
+                record.reschedule();
+            }
+        }
+    }
+
+    pub fn priority(&self, word: &str, now: DateTime<Utc>) -> Priority {
+        let word = word.trim().to_lowercase();
+        match self.get(&word).and_then(|record| record.next_review) {
+            None => Priority::New,
+            Some(next_review) if next_review <= now => Priority::Due(next_review),
+            Some(next_review) => Priority::Later(next_review),
+        }
+    }
+
+    /// The answered words due for review as of `now`.
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<&WordStatistic> {
+        let mut due: Vec<&WordStatistic> = self
+            .records
+            .iter()
+            .filter(|record| record.all > 0 && record.is_due(now))
+            .collect();
+        due.sort_by_key(|record| record.next_review);
+        due
+    }
+}
+
+#[cfg(test)]
+mod scheduler_tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{quality, Priority, INITIAL_EASE_FACTOR, MINIMUM_EASE_FACTOR, PASSING_SCORE};
+    use crate::stats::{Outcome, Statistic, WordStatistic, STATISTIC_FILE};
+
+    #[test]
+    fn quality_test() {
+        assert_eq!(5, quality(Outcome::Success, 100, 2));
+        assert_eq!(4, quality(Outcome::Success, 100, 20));
+        assert_eq!(4, quality(Outcome::Success, 95, 2));
+        assert_eq!(3, quality(Outcome::Success, 95, 20));
+        assert_eq!(3, quality(Outcome::Success, 80, 20));
+        assert_eq!(1, quality(Outcome::Error, 30, 2));
+        assert_eq!(0, quality(Outcome::Timeout, 0, 30));
+    }
+
+    #[test]
+    fn outcome_boundary_test() {
+        assert_eq!(2, quality(Outcome::Error, 80, 2));
+        assert_eq!(2, quality(Outcome::Error, PASSING_SCORE - 1, 2));
+        assert_eq!(3, quality(Outcome::Success, PASSING_SCORE, 20));
+        assert_eq!(3, quality(Outcome::Success, 70, 2));
+
+        let mut record = WordStatistic::new("stifle");
+        record.add(Outcome::Success, 100, 2);
+        record.add(Outcome::Success, 100, 2);
+        assert_eq!((6, 2), (record.interval, record.repetitions));
+        record.add(Outcome::Error, 80, 2);
+        assert_eq!((1, 0), (record.interval, record.repetitions));
+    }
+
+    #[test]
+    fn review_test() {
+        let now = Utc.with_ymd_and_hms(2021, 12, 9, 8, 0, 0).unwrap();
+        let mut record = WordStatistic::new("stifle");
+        assert_eq!(INITIAL_EASE_FACTOR, record.ease_factor);
+        assert!(record.is_due(now));
+
+        record.review(5, now);
+        assert_eq!((1, 1), (record.interval, record.repetitions));
+        record.review(5, now);
+        assert_eq!((6, 2), (record.interval, record.repetitions));
+        record.review(4, now);
+        assert_eq!((16, 3), (record.interval, record.repetitions));
+        assert!((record.ease_factor - 2.7).abs() < 1e-9);
+        assert_eq!(Some(now + Duration::days(16)), record.next_review);
+        assert!(!record.is_due(now + Duration::days(15)));
+        assert!(record.is_due(now + Duration::days(16)));
+
+        record.review(0, now);
+        assert_eq!((1, 0), (record.interval, record.repetitions));
+        assert!((record.ease_factor - 1.9).abs() < 1e-9);
+        for _ in 0..5 {
+            record.review(0, now);
+        }
+        assert_eq!(MINIMUM_EASE_FACTOR, record.ease_factor);
+    }
+
+    #[test]
+    fn priority_test() {
+        let now = Utc::now();
+        let mut statistic = Statistic::default();
+        statistic.record("defer", Outcome::Success, 100, 2);
+        statistic.record("stance", Outcome::Error, 10, 20);
+        statistic.records[1].next_review = Some(now - Duration::days(1));
+        statistic.record("anger", Outcome::Timeout, 0, 30);
+        statistic.records[2].next_review = Some(now - Duration::days(3));
+
+        assert!(matches!(statistic.priority("defer", now), Priority::Later(_)));
+        assert!(matches!(statistic.priority("Stance", now), Priority::Due(_)));
+        assert_eq!(Priority::New, statistic.priority("outlier", now));
+        let mut words = vec!["defer", "outlier", "stance", "anger"];
+        words.sort_by_key(|word| statistic.priority(word, now));
+        assert_eq!(vec!["anger", "stance", "outlier", "defer"], words);
+        let due: Vec<&str> = statistic.due(now).iter().map(|record| record.word.as_str()).collect();
+        assert_eq!(vec!["anger", "stance"], due);
+    }
+
+    #[test]
+    fn reschedule_test() {
+        let statistic = Statistic::load(&format!("../{}", STATISTIC_FILE)).unwrap();
+        for record in &statistic.records {
+            let next_review = record.next_review.unwrap();
+            assert!(next_review > record.updated_on);
+            assert!(next_review >= record.created_on + Duration::days(record.interval as i64));
+        }
+        let stance = statistic.get("stance").unwrap();
+        assert!(stance.ease_factor < INITIAL_EASE_FACTOR);
+    }
+}
@@ -0,0 +1,359 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+/// Shortest word `stem` leaves after dropping an inflection.
+pub const MIN_STEM_LENGTH: usize = 4;
+
+/// How an answer is compared with the expected text.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Algorithm {
+    Levenshtein,
+    /// Levenshtein that also counts swapped neighbours as one typo.
+    #[default]
+    Damerau,
+    /// Rewards a common beginning, so it is the most lenient with cut-off answers.
+    JaroWinkler,
+    /// Word order and repeated words do not matter.
+    TokenSet,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "levenshtein" => Ok(Algorithm::Levenshtein),
+            "damerau" => Ok(Algorithm::Damerau),
+            "jarowinkler" => Ok(Algorithm::JaroWinkler),
+            "tokenset" => Ok(Algorithm::TokenSet),
+            _ => Err("expected one of: levenshtein, damerau, jaro-winkler, token-set".to_string()),
+        }
+    }
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// turning one text into the other.
+pub fn levenshtein(first: &str, second: &str) -> usize {
+    let second: Vec<char> = second.chars().collect();
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    for (i, a) in first.chars().enumerate() {
+        let mut current = vec![i + 1; second.len() + 1];
+        for (j, b) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[second.len()]
+}
+
+/// Levenshtein distance where swapping two neighbouring characters is one
+/// edit (optimal string alignment).
+pub fn damerau(first: &str, second: &str) -> usize {
+    let first: Vec<char> = first.chars().collect();
+    let second: Vec<char> = second.chars().collect();
+    let mut distances = vec![vec![0; second.len() + 1]; first.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=first.len() {
+        for j in 1..=second.len() {
+            let cost = usize::from(first[i - 1] != second[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && first[i - 1] == second[j - 2] && first[i - 2] == second[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[first.len()][second.len()]
+}
+
+/// Jaro similarity between 0 and 1.
+pub fn jaro(first: &str, second: &str) -> f64 {
+    let first: Vec<char> = first.chars().collect();
+    let second: Vec<char> = second.chars().collect();
+    if first.is_empty() && second.is_empty() {
+        return 1.0;
+    }
+    if first.is_empty() || second.is_empty() {
+        return 0.0;
+    }
+    let window = (first.len().max(second.len()) / 2).saturating_sub(1);
+    let mut first_matches = vec![false; first.len()];
+    let mut second_matches = vec![false; second.len()];
+    let mut matches = 0;
+    for (i, a) in first.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(second.len());
+        for j in start..end {
+            if !second_matches[j] && second[j] == *a {
+                first_matches[i] = true;
+                second_matches[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let first_matched = first.iter().zip(&first_matches).filter(|(_, matched)| **matched);
+    let second_matched = second.iter().zip(&second_matches).filter(|(_, matched)| **matched);
+    let transpositions = first_matched
+        .zip(second_matched)
+        .filter(|((a, _), (b, _))| a != b)
+        .count()
+        / 2;
+    let matches = matches as f64;
+    (matches / first.len() as f64 + matches / second.len() as f64 + (matches - transpositions as f64) / matches) / 3.0
+}
+
+/// Jaro similarity boosted by a common prefix of up to four characters.
+pub fn jaro_winkler(first: &str, second: &str) -> f64 {
+    let similarity = jaro(first, second);
+    let prefix = first
+        .chars()
+        .zip(second.chars())
+        .take(4)
+        .take_while(|(a, b)| a == b)
+        .count();
+    similarity + prefix as f64 * 0.1 * (1.0 - similarity)
+}
+
+/// Compares the common words with the expected words and the two sorted
+/// word sets with each other, keeping the best ratio. Extra words in the
+/// answer (`beef it up`) don't count against it, missing ones (`up`) do.
+pub fn token_set(expected: &str, answer: &str) -> f64 {
+    let expected: BTreeSet<&str> = expected.split_whitespace().collect();
+    let answer: BTreeSet<&str> = answer.split_whitespace().collect();
+    let join = |words: Vec<&str>| words.join(" ");
+    let common = join(expected.intersection(&answer).cloned().collect());
+    let expected_rest = join(expected.difference(&answer).cloned().collect());
+    let answer_rest = join(answer.difference(&expected).cloned().collect());
+    let with = |rest: &str| join([common.as_str(), rest].into_iter().filter(|part| !part.is_empty()).collect());
+    let expected_all = with(&expected_rest);
+    let answer_all = with(&answer_rest);
+
+    [
+        ratio(&expected_all, &answer_all),
+        if common.is_empty() { 0.0 } else { ratio(&common, &expected_all) },
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+fn ratio(first: &str, second: &str) -> f64 {
+    let longest = first.chars().count().max(second.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(first, second) as f64 / longest as f64
+}
+
+/// Lower case, single spaces, hyphens as spaces and no punctuation around words.
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .replace('-', " ")
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Drops the regular inflection of an English word: `deferred`, `defers`
+/// and `deferring` become `defer`, `studies` and `studied` become `study`.
+/// Whatever is left must keep `MIN_STEM_LENGTH` characters, so short or
+/// truncated words (`news`, `string`) are not stemmed into other words.
+pub fn stem(word: &str) -> String {
+    let long_enough = |base: &str| base.chars().count() >= MIN_STEM_LENGTH;
+    let found = ["ies", "ied", "ing", "ed", "es", "s"]
+        .iter()
+        .filter_map(|suffix| word.strip_suffix(suffix).map(|base| (base, *suffix)))
+        .find(|(base, _)| long_enough(base));
+    let base = match found {
+        Some((_, "s")) if word.ends_with("ss") => word.to_string(),
+        Some((base, "ies" | "ied")) => format!("{}y", base),
+        Some((base, _)) => {
+            let chars: Vec<char> = base.chars().collect();
+            let last = chars[chars.len() - 1];
+            if chars.len() > MIN_STEM_LENGTH && last == chars[chars.len() - 2] && !"aeiouls".contains(last) {
+                chars[..chars.len() - 1].iter().collect()
+            } else {
+                base.to_string()
+            }
+        }
+        None => word.to_string(),
+    };
+    match base.strip_suffix('e') {
+        Some(without) if long_enough(without) => without.to_string(),
+        _ => base,
+    }
+}
+
+/// Every word of the normalized text stemmed, e.g. `Beefed-up` -> `beef up`.
+pub fn lemmatize(text: &str) -> String {
+    normalize(text).split(' ').map(stem).collect::<Vec<_>>().join(" ")
+}
+
+fn similarity(algorithm: Algorithm, expected: &str, answer: &str) -> f64 {
+    match algorithm {
+        Algorithm::Levenshtein => ratio(expected, answer),
+        Algorithm::Damerau => {
+            let longest = expected.chars().count().max(answer.chars().count());
+            if longest == 0 {
+                1.0
+            } else {
+                1.0 - damerau(expected, answer) as f64 / longest as f64
+            }
+        }
+        Algorithm::JaroWinkler => jaro_winkler(expected, answer),
+        Algorithm::TokenSet => token_set(expected, answer),
+    }
+}
+
+/// How close the answer is to the expected text: 100 is a full match, 0
+/// nothing in common. Case, punctuation, hyphens, the spaces of a phrasal
+/// verb (`beefup`) and regular inflections (`beefed up`) do not count.
+pub fn score_with(algorithm: Algorithm, expected: &str, answer: &str) -> u8 {
+    let expected = normalize(expected);
+    let answer = normalize(answer);
+    if expected.is_empty() || answer.is_empty() {
+        return if expected == answer { 100 } else { 0 };
+    }
+    let joined = |text: &str| text.replace(' ', "");
+    let best = [
+        similarity(algorithm, &expected, &answer),
+        similarity(algorithm, &lemmatize(&expected), &lemmatize(&answer)),
+        similarity(algorithm, &joined(&expected), &joined(&answer)),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max);
+    (100.0 * best).round().clamp(0.0, 100.0) as u8
+}
+
+/// The score of the answer against the closest of the expected texts.
+pub fn best_score_with<S: AsRef<str>>(algorithm: Algorithm, expected: &[S], answer: &str) -> u8 {
+    expected
+        .iter()
+        .map(|text| score_with(algorithm, text.as_ref(), answer))
+        .max()
+        .unwrap_or(0)
+}
+
+/// `score_with` the default algorithm.
+pub fn score(expected: &str, answer: &str) -> u8 {
+    score_with(Algorithm::default(), expected, answer)
+}
+
+/// `best_score_with` the default algorithm.
+pub fn best_score<S: AsRef<str>>(expected: &[S], answer: &str) -> u8 {
+    best_score_with(Algorithm::default(), expected, answer)
+}
+
+#[cfg(test)]
+mod scoring_tests {
+    use super::{
+        best_score, damerau, jaro_winkler, lemmatize, levenshtein, normalize, score, score_with, stem, token_set,
+        Algorithm,
+    };
+
+    #[test]
+    fn levenshtein_test() {
+        assert_eq!(0, levenshtein("defer", "defer"));
+        assert_eq!(3, levenshtein("kitten", "sitting"));
+        assert_eq!(5, levenshtein("", "defer"));
+        assert_eq!(1, levenshtein("café", "cafe"));
+        assert_eq!(2, levenshtein("defer", "dfeer"));
+        assert_eq!(1, damerau("defer", "dfeer"));
+        assert_eq!(3, damerau("kitten", "sitting"));
+        assert_eq!(0, damerau("", ""));
+    }
+
+    #[test]
+    fn jaro_winkler_test() {
+        assert!((jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((jaro_winkler("dwayne", "duane") - 0.84).abs() < 0.001);
+        assert_eq!(1.0, jaro_winkler("defer", "defer"));
+        assert_eq!(0.0, jaro_winkler("abc", "xyz"));
+        assert_eq!(0.0, jaro_winkler("", "defer"));
+    }
+
+    #[test]
+    fn token_set_test() {
+        assert_eq!(1.0, token_set("beef up", "up beef"));
+        assert_eq!(1.0, token_set("beef up", "beef it up"));
+        assert!(token_set("beef up", "wind up") < 1.0);
+        assert_eq!(0.0, token_set("abc", "xyz"));
+        assert!(token_set("beef up", "up") < 0.5);
+        assert!(token_set("beef up", "beef") < 0.85);
+    }
+
+    #[test]
+    fn stem_test() {
+        assert_eq!("defer", stem("deferred"));
+        assert_eq!("defer", stem("defers"));
+        assert_eq!("defer", stem("deferring"));
+        assert_eq!("study", stem("studies"));
+        assert_eq!("stifl", stem("stifled"));
+        assert_eq!("stifl", stem("stifle"));
+        assert_eq!("class", stem("class"));
+        assert_eq!("is", stem("is"));
+        assert_eq!("stanc", stem("stance"));
+        assert_eq!("take", stem("takes"));
+        assert_eq!("stop", stem("stopped"));
+        assert_eq!("news", stem("news"));
+        assert_eq!("string", stem("string"));
+        assert_eq!("acting", stem("acting"));
+        assert_eq!("beef up", lemmatize("Beefed-up"));
+    }
+
+    #[test]
+    fn score_test() {
+        assert_eq!("wind up", normalize("  Wind   UP. "));
+        assert_eq!(100, score("Wind up", "wind  up"));
+        assert_eq!(100, score("beef up", "beefup"));
+        assert_eq!(100, score("beef up", "Beefs up"));
+        assert_eq!(100, score("defer", "deferred"));
+        assert_eq!(80, score("defer", "defar"));
+        assert_eq!(80, score("defer", "dfeer"));
+        assert_eq!(60, score_with(Algorithm::Levenshtein, "defer", "dfeer"));
+        assert!(score("beef up", "bef up") >= 85);
+        assert_eq!(0, score("defer", ""));
+        assert_eq!(100, score("", ""));
+        assert_eq!(100, score_with(Algorithm::TokenSet, "beef up", "up beef"));
+        assert!(score_with(Algorithm::JaroWinkler, "stifle", "stiffle") > 90);
+        assert_eq!(
+            100,
+            best_score(&["to delay something", "to postpone"], "To postpone.")
+        );
+        assert_eq!(0, best_score::<&str>(&[], "defer"));
+    }
+
+    #[test]
+    fn truncated_answer_test() {
+        for algorithm in [Algorithm::Levenshtein, Algorithm::Damerau, Algorithm::TokenSet] {
+            assert!(score_with(algorithm, "beef up", "up") < 85, "{:?}", algorithm);
+            assert!(score_with(algorithm, "string", "str") < 85, "{:?}", algorithm);
+            assert!(score_with(algorithm, "news", "new") < 85, "{:?}", algorithm);
+        }
+        assert!(score_with(Algorithm::JaroWinkler, "beef up", "up") < 85);
+        assert!(score("wind up", "wind") < 85);
+        assert_eq!(100, score("take", "takes"));
+    }
+
+    #[test]
+    fn algorithm_test() {
+        assert_eq!(Ok(Algorithm::JaroWinkler), "Jaro-Winkler".parse());
+        assert_eq!(Ok(Algorithm::TokenSet), "token_set".parse());
+        assert_eq!(Ok(Algorithm::Damerau), "damerau".parse());
+        assert!("soundex".parse::<Algorithm>().is_err());
+        assert_eq!(Algorithm::Damerau, Algorithm::default());
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    fmt, fs,
+    io::ErrorKind,
+    path::Path,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    scheduler::{quality, INITIAL_EASE_FACTOR},
+};
+
+pub const STATISTIC_FILE: &str = "data/stat/statistic.json";
+/// How long `Statistic::update` waits for another session to release the file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// A lock older than this was left behind by a session that died while saving.
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Success,
+    Error,
+    Timeout,
+}
+
+/// Every answer given to a word: its score and how many seconds it took,
+/// and when the word should be reviewed again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordStatistic {
+    pub word: String,
+    pub all: u32,
+    pub error_cnt: u32,
+    pub success_cnt: u32,
+    pub timeout_cnt: u32,
+    pub scores: Vec<u8>,
+    pub respond_times: Vec<u64>,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
+    #[serde(default = "initial_ease_factor")]
+    pub ease_factor: f64,
+    /// Days between the last and the next review.
+    #[serde(default)]
+    pub interval: u32,
+    /// Correct answers in a row.
+    #[serde(default)]
+    pub repetitions: u32,
+    #[serde(default)]
+    pub next_review: Option<DateTime<Utc>>,
+}
+
+fn initial_ease_factor() -> f64 {
+    INITIAL_EASE_FACTOR
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statistic {
+    pub records: Vec<WordStatistic>,
+}
+
+impl WordStatistic {
+    pub fn new(word: &str) -> Self {
+        let now = Utc::now();
+        WordStatistic {
+            word: word.to_string(),
+            all: 0,
+            error_cnt: 0,
+            success_cnt: 0,
+            timeout_cnt: 0,
+            scores: vec![],
+            respond_times: vec![],
+            created_on: now,
+            updated_on: now,
+            ease_factor: INITIAL_EASE_FACTOR,
+            interval: 0,
+            repetitions: 0,
+            next_review: None,
+        }
+    }
+
+    pub fn add(&mut self, outcome: Outcome, score: u8, respond_time: u64) {
+        match outcome {
+            Outcome::Success => self.success_cnt += 1,
+            Outcome::Error => self.error_cnt += 1,
+            Outcome::Timeout => self.timeout_cnt += 1,
+        }
+        self.all += 1;
+        self.scores.push(score);
+        self.respond_times.push(respond_time);
+        self.updated_on = Utc::now();
+        self.review(quality(outcome, score, respond_time), self.updated_on);
+    }
+
+    pub fn average_score(&self) -> f64 {
+        average(&self.scores.iter().map(|score| *score as u64).collect::<Vec<_>>())
+    }
+
+    pub fn average_respond_time(&self) -> f64 {
+        average(&self.respond_times)
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.all == 0 {
+            0.0
+        } else {
+            self.success_cnt as f64 / self.all as f64
+        }
+    }
+
+    /// Average score of the later half of the answers minus the earlier
+    /// half; positive when the word is getting easier.
+    pub fn trend(&self) -> f64 {
+        if self.scores.len() < 2 {
+            return 0.0;
+        }
+        let (earlier, later) = self.scores.split_at(self.scores.len() / 2);
+        let later_average = average(&later.iter().map(|score| *score as u64).collect::<Vec<_>>());
+        let earlier_average = average(&earlier.iter().map(|score| *score as u64).collect::<Vec<_>>());
+        later_average - earlier_average
+    }
+}
+
+impl Statistic {
+    /// The statistic saved in the file; an empty one when there is no file yet.
+    pub fn load(file: &str) -> ServiceExuctionResult<Self> {
+        if !Path::new(file).exists() {
+            return Ok(Statistic::default());
+        }
+        let content = fs::read_to_string(file)?;
+        let mut statistic: Statistic = serde_json::from_str(&content).map_err(|err| ServiceError {
+            message: format!("invalid.statistic: {} -> {}", file, err),
+            error_type: ServiceErrorType::SerializationError,
+        })?;
+        statistic.schedule_missing();
+        Ok(statistic)
+    }
+
+    /// Writes a temporary file next to the statistic and renames it, so an
+    /// interrupted save never leaves half a file behind. The temporary name is
+    /// unique per process and save, so concurrent saves never share it.
+    pub fn save(&self, file: &str) -> ServiceExuctionResult<()> {
+        if let Some(dir) = Path::new(file).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|err| ServiceError {
+            message: format!("invalid.statistic: {}", err),
+            error_type: ServiceErrorType::SerializationError,
+        })?;
+        let tmp_file = format!(
+            "{}.{}.{}.tmp",
+            file,
+            process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        if let Err(err) = fs::write(&tmp_file, content).and_then(|_| fs::rename(&tmp_file, file)) {
+            let _ = fs::remove_file(&tmp_file);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// Loads the statistic, applies the change and saves it while holding
+    /// `<file>.lock`, so answers of concurrent sessions are not lost.
+    pub fn update<F: FnOnce(&mut Statistic)>(file: &str, change: F) -> ServiceExuctionResult<Statistic> {
+        let _lock = FileLock::acquire(file)?;
+        let mut statistic = Statistic::load(file)?;
+        change(&mut statistic);
+        statistic.save(file)?;
+        Ok(statistic)
+    }
+
+    pub fn get(&self, word: &str) -> Option<&WordStatistic> {
+        let word = word.trim().to_lowercase();
+        self.records.iter().find(|record| record.word == word)
+    }
+
+    /// Adds the answer to the record of the word, creating it on the first answer.
+    pub fn record(&mut self, word: &str, outcome: Outcome, score: u8, respond_time: u64) {
+        let word = word.trim().to_lowercase();
+        let index = match self.records.iter().position(|record| record.word == word) {
+            Some(index) => index,
+            None => {
+                self.records.push(WordStatistic::new(&word));
+                self.records.len() - 1
+            }
+        };
+        self.records[index].add(outcome, score, respond_time);
+    }
+
+    /// The overall numbers and the `hardest` words with the lowest average score.
+    pub fn report(&self, hardest: usize) -> Report {
+        let answered: Vec<&WordStatistic> = self.records.iter().filter(|record| record.all > 0).collect();
+        let answers: u32 = answered.iter().map(|record| record.all).sum();
+        let scores: Vec<u64> = answered
+            .iter()
+            .flat_map(|record| record.scores.iter().map(|score| *score as u64))
+            .collect();
+        let respond_times: Vec<u64> = answered.iter().flat_map(|record| record.respond_times.clone()).collect();
+        let trends: Vec<f64> = answered
+            .iter()
+            .filter(|record| record.scores.len() > 1)
+            .map(|record| record.trend())
+            .collect();
+
+        let mut by_score = answered.clone();
+        by_score.sort_by(|a, b| a.average_score().total_cmp(&b.average_score()).then(a.word.cmp(&b.word)));
+        Report {
+            words: answered.len(),
+            answers,
+            success_cnt: answered.iter().map(|record| record.success_cnt).sum(),
+            timeout_cnt: answered.iter().map(|record| record.timeout_cnt).sum(),
+            average_score: average(&scores),
+            average_respond_time: average(&respond_times),
+            trend: if trends.is_empty() { 0.0 } else { trends.iter().sum::<f64>() / trends.len() as f64 },
+            hardest: by_score
+                .into_iter()
+                .take(hardest)
+                .map(|record| (record.word.clone(), record.average_score()))
+                .collect(),
+        }
+    }
+}
+
+/// Exclusive `<file>.lock` next to the statistic, removed when dropped.
+struct FileLock {
+    path: String,
+}
+
+impl FileLock {
+    fn acquire(file: &str) -> ServiceExuctionResult<Self> {
+        if let Some(dir) = Path::new(file).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let path = format!("{}.lock", file);
+        let started = Instant::now();
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(FileLock { path }),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .map(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK)
+                        .unwrap_or(false);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                    } else if started.elapsed() > LOCK_TIMEOUT {
+                        return Err(ServiceError {
+                            message: format!("statistic.locked: {}", path),
+                            error_type: ServiceErrorType::Timeout,
+                        });
+                    } else {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Summary of all recorded answers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub words: usize,
+    pub answers: u32,
+    pub success_cnt: u32,
+    pub timeout_cnt: u32,
+    pub average_score: f64,
+    /// In seconds.
+    pub average_respond_time: f64,
+    /// Average change of the words' scores, see `WordStatistic::trend`.
+    pub trend: f64,
+    /// Word and its average score, the lowest first.
+    pub hardest: Vec<(String, f64)>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} words, {} answers, {} passed, {} timed out",
+            self.words, self.answers, self.success_cnt, self.timeout_cnt
+        )?;
+        writeln!(
+            f,
+            "average score: {:.1}, average response time: {:.1}s, trend: {:+.1}",
+            self.average_score, self.average_respond_time, self.trend
+        )?;
+        if !self.hardest.is_empty() {
+            let hardest: Vec<String> = self
+                .hardest
+                .iter()
+                .map(|(word, score)| format!("{} ({:.0})", word, score))
+                .collect();
+            write!(f, "hardest: {}", hardest.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn average(values: &[u64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<u64>() as f64 / values.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::{Outcome, Statistic, WordStatistic, STATISTIC_FILE};
+    use crate::files::temp_dir;
+
+    fn temp_file(name: &str) -> String {
+        format!("{}/{}", temp_dir(format!("common_libs_stats_files/{}", name)), name)
+    }
+
+    #[test]
+    fn load_test() {
+        let statistic = Statistic::load(&format!("../{}", STATISTIC_FILE)).unwrap();
+        assert!(!statistic.records.is_empty());
+        for record in &statistic.records {
+            assert_eq!(record.all as usize, record.scores.len());
+            assert_eq!(record.all as usize, record.respond_times.len());
+            assert_eq!(record.all, record.success_cnt + record.error_cnt + record.timeout_cnt);
+        }
+        assert_eq!(Statistic::default(), Statistic::load("missing/statistic.json").unwrap());
+    }
+
+    #[test]
+    fn record_test() {
+        let file = temp_file("statistic.json");
+        let mut statistic = Statistic::default();
+        statistic.record("Defer", Outcome::Success, 100, 3);
+        statistic.record("defer", Outcome::Timeout, 0, 30);
+        statistic.record("stifle", Outcome::Error, 40, 12);
+        statistic.save(&file).unwrap();
+
+        let saved = Statistic::load(&file).unwrap();
+        assert_eq!(statistic, saved);
+        let defer = saved.get("defer").unwrap();
+        assert_eq!((2, 1, 0, 1), (defer.all, defer.success_cnt, defer.error_cnt, defer.timeout_cnt));
+        assert_eq!(vec![100, 0], defer.scores);
+        assert_eq!(vec![3, 30], defer.respond_times);
+        assert!(saved.get("missing").is_none());
+        assert_eq!(Some(defer), saved.get(" Defer"));
+    }
+
+    #[test]
+    fn update_test() {
+        let file = temp_file("update.json");
+        Statistic::update(&file, |statistic| statistic.record("defer", Outcome::Success, 90, 4)).unwrap();
+        let statistic = Statistic::update(&file, |statistic| statistic.record("defer", Outcome::Error, 50, 8)).unwrap();
+        assert_eq!(statistic, Statistic::load(&file).unwrap());
+        assert_eq!(2, statistic.get("defer").unwrap().all);
+        assert!(!std::path::Path::new(&format!("{}.lock", file)).exists());
+        let dir = std::path::Path::new(&file).parent().unwrap();
+        assert_eq!(1, std::fs::read_dir(dir).unwrap().count());
+    }
+
+    #[test]
+    fn concurrent_update_test() {
+        let file = temp_file("concurrent.json");
+        let sessions: Vec<_> = (0..8)
+            .map(|session| {
+                let file = file.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..5 {
+                        Statistic::update(&file, |statistic| {
+                            statistic.record(&format!("word{}", session), Outcome::Success, 100, 2)
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for session in sessions {
+            session.join().unwrap();
+        }
+
+        let statistic = Statistic::load(&file).unwrap();
+        assert_eq!(8, statistic.records.len());
+        assert!(statistic.records.iter().all(|record| record.all == 5));
+    }
+
+    #[test]
+    fn aggregates_test() {
+        let mut record = WordStatistic::new("stifle");
+        assert_eq!((0.0, 0.0, 0.0), (record.average_score(), record.success_rate(), record.trend()));
+        record.add(Outcome::Error, 40, 16);
+        record.add(Outcome::Success, 90, 2);
+        record.add(Outcome::Success, 100, 3);
+        record.add(Outcome::Success, 100, 3);
+        assert_eq!(82.5, record.average_score());
+        assert_eq!(6.0, record.average_respond_time());
+        assert_eq!(0.75, record.success_rate());
+        assert_eq!(35.0, record.trend());
+    }
+
+    #[test]
+    fn report_test() {
+        let mut statistic = Statistic::default();
+        statistic.record("defer", Outcome::Success, 100, 2);
+        statistic.record("defer", Outcome::Success, 100, 4);
+        statistic.record("stifle", Outcome::Error, 20, 10);
+        statistic.record("stifle", Outcome::Success, 80, 4);
+        statistic.record("stance", Outcome::Timeout, 0, 30);
+        let report = statistic.report(2);
+        assert_eq!((3, 5, 3, 1), (report.words, report.answers, report.success_cnt, report.timeout_cnt));
+        assert_eq!(60.0, report.average_score);
+        assert_eq!(10.0, report.average_respond_time);
+        assert_eq!(30.0, report.trend);
+        assert_eq!(vec![("stance".to_string(), 0.0), ("stifle".to_string(), 50.0)], report.hardest);
+        assert!(report.to_string().contains("hardest: stance (0), stifle (50)"));
+
+        let saved = Statistic::load(&format!("../{}", STATISTIC_FILE)).unwrap();
+        let report = saved.report(3);
+        assert_eq!(saved.records.len(), report.words);
+        assert_eq!(3, report.hardest.len());
+        assert_eq!(Statistic::default().report(3), Default::default());
+    }
+}
@@ -1,12 +1,25 @@
 use common_libs::{self, configure_log4rs};
 use executor::model::actor::{start_actor, exchange_command, SystemCommand};
 use futures::{FutureExt, stream, StreamExt};
+use log::error;
+use mk_scraper::{
+    registry,
+    scraper_config::SCRAPERS_CONFIG,
+    wiktionary::{self, WIKTIONARY_DUMP},
+};
 
 use tokio::spawn;
 
 #[tokio::main]
 async fn main() {
     configure_log4rs();
+    if let Err(err) = registry::load_config(SCRAPERS_CONFIG) {
+        error!("invalid.scrapers.config: {}", err.message);
+        std::process::exit(1);
+    }
+    if let Err(err) = wiktionary::register(WIKTIONARY_DUMP) {
+        error!("invalid.wiktionary.dump: {}", err.message);
+    }
     let arnold = "arnold".to_string();
     let silvester = "silvester".to_string(); 
 
@@ -155,7 +155,7 @@ pub mod actor {
     
     use futures::join;
     use log::{error, info};
-    use mk_scraper::{scrape_all, tokio_scrape_all};
+    use mk_scraper::{scrape_all, scrape_report::ScrapeReport};
     use serde::{de::DeserializeOwned, Deserialize, Serialize};
     use serde_json::Map;
     use std::{
@@ -313,7 +313,7 @@ pub mod actor {
     struct WordListHander {}
 
     impl WordListReq {
-        async fn do_it(list: Vec<String>) {
+        async fn do_it(list: Vec<String>) -> ScrapeReport {
             scrape_all(list).await
         }
     }
@@ -323,13 +323,13 @@ pub mod actor {
 
         fn handle(self, request: WordListReq) -> ProcessResult<Self::Output> {
             info!("message is being handled...");
-            runtime::Runtime::new().unwrap().block_on(async move {
-                let _value = tokio::spawn(tokio_scrape_all(request.words)).await.unwrap();
-            });
-            info!("scrape_all should be spawned...");
+            let report = runtime::Runtime::new()
+                .unwrap()
+                .block_on(WordListReq::do_it(request.words));
+            info!("scrape_all: {}", report.summary());
             Ok(WordListRes {
-                processed: vec![],
-                not_found: vec![],
+                processed: report.found_words(),
+                not_found: report.missing_words(),
             })
         }
     }
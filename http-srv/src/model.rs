@@ -1,5 +1,6 @@
-use std::iter::Map;
+use std::collections::HashMap;
 
+use mk_scraper::scrape_report::ScrapeReport;
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -7,8 +8,35 @@ pub struct DownloadRequest {
     pub words: Vec<String>
 }
 
+/// Words per dictionary name.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Report {
     pub status: String,
-    pub not_found: Map<String, Vec<String>>,
-    pub found: Map<String, Vec<String>>
-}
\ No newline at end of file
+    pub not_found: HashMap<String, Vec<String>>,
+    pub found: HashMap<String, Vec<String>>,
+    pub errors: HashMap<String, Vec<String>>,
+}
+
+impl From<ScrapeReport> for Report {
+    fn from(report: ScrapeReport) -> Self {
+        let status = if report.errors.is_empty() { "ok" } else { "errors" };
+        let found = report
+            .found
+            .keys()
+            .map(|dictionary| (dictionary.clone(), report.found_in(dictionary)))
+            .collect();
+        let errors = report
+            .errors
+            .iter()
+            .map(|(dictionary, errors)| {
+                (dictionary.clone(), errors.iter().map(|(word, _)| word.clone()).collect())
+            })
+            .collect();
+        Report {
+            status: status.to_string(),
+            not_found: report.not_found,
+            found,
+            errors,
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use actix_web::{HttpResponse, post};
 use actix_web::web::Json;
+use mk_scraper::scrape_all;
 
-
-use crate::model::DownloadRequest;
+use crate::model::{DownloadRequest, Report};
 
 #[post("/")]
-pub async fn download(_words: Json<DownloadRequest>) -> HttpResponse {
-    HttpResponse::Accepted().body("Hello, Mr. World")
-}
\ No newline at end of file
+pub async fn download(words: Json<DownloadRequest>) -> HttpResponse {
+    let report = scrape_all(words.into_inner().words).await;
+    HttpResponse::Ok().json(Report::from(report))
+}
@@ -0,0 +1,272 @@
+use std::{fs, path::Path};
+
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::{file_name, save},
+};
+use log::{error, info};
+
+use crate::{
+    constants::{JSON_EXT, MP3_EXT},
+    dictionary_scraper::not_found,
+    http_client::Fetcher,
+    model::{Dictionary, DictionaryEntry},
+};
+
+pub const MP3_DIR: &str = "mp3";
+pub const JSON_DIR: &str = "json";
+const ID3_TAG: &[u8] = b"ID3";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioReport {
+    pub downloaded: Vec<String>,
+    /// Already on disk.
+    pub skipped: Vec<String>,
+    /// Entries without any pronunciation link.
+    pub no_audio: Vec<String>,
+    /// Link and reason of the downloads that failed or were not MP3s.
+    pub broken: Vec<(String, String)>,
+}
+
+impl AudioReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} downloaded, {} skipped, {} without audio, {} broken links",
+            self.downloaded.len(),
+            self.skipped.len(),
+            self.no_audio.len(),
+            self.broken.len()
+        )
+    }
+}
+
+/// `<data_dir>/<dictionary>/mp3`, e.g. `data/en-en/cambridge/mp3`.
+pub fn mp3_dir(data_dir: &str, dictionary: &Dictionary) -> String {
+    [data_dir, &dictionary.name(), MP3_DIR].join("/")
+}
+
+pub fn mp3_file(data_dir: &str, entry: &DictionaryEntry) -> String {
+    let word = entry.word.trim().to_lowercase();
+    file_name(mp3_dir(data_dir, &entry.source).as_str(), word.as_str(), MP3_EXT)
+}
+
+/// An ID3 tag or an MPEG audio frame sync at the start of the file.
+pub fn is_mp3(content: &[u8]) -> bool {
+    content.starts_with(ID3_TAG) || (content.len() > 1 && content[0] == 0xFF && content[1] & 0xE0 == 0xE0)
+}
+
+fn is_mp3_file(file_name: &str) -> bool {
+    match fs::read(file_name) {
+        Ok(content) => is_mp3(&content),
+        Err(_) => false,
+    }
+}
+
+/// The dictionary's own link first, then the UK and the US ones.
+pub fn audio_link(entry: &DictionaryEntry) -> Option<String> {
+    entry
+        .mp3_link
+        .clone()
+        .or_else(|| entry.uk.as_ref().and_then(|uk| uk.mp3_link.clone()))
+        .or_else(|| entry.us.as_ref().and_then(|us| us.mp3_link.clone()))
+}
+
+/// Saves the pronunciation of the entry under `data_dir` and sets its `file`.
+/// Returns false when a valid file was already there.
+pub async fn download_audio(
+    fetcher: &dyn Fetcher,
+    entry: &mut DictionaryEntry,
+    data_dir: &str,
+) -> ServiceExuctionResult<bool> {
+    let file = mp3_file(data_dir, entry);
+    if is_mp3_file(&file) {
+        entry.file = Some(file);
+        return Ok(false);
+    }
+
+    let link = audio_link(entry).ok_or_else(|| not_found("mp3.link.not.found"))?;
+    let content = fetcher.get_bytes(&link).await?;
+    if !is_mp3(&content) {
+        return Err(ServiceError {
+            message: format!("invalid.mp3: {}", link),
+            error_type: ServiceErrorType::Failure,
+        });
+    }
+
+    if let Some(dir) = Path::new(&file).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp_file = [file.as_str(), ".tmp"].join("");
+    fs::write(&tmp_file, content)?;
+    fs::rename(&tmp_file, &file)?;
+    info!("audio.saved: {} -> {}", link, file);
+    entry.file = Some(file);
+    Ok(true)
+}
+
+pub async fn download_all(fetcher: &dyn Fetcher, entries: &mut [DictionaryEntry], data_dir: &str) -> AudioReport {
+    let mut report = AudioReport::default();
+    for entry in entries.iter_mut() {
+        let word = entry.word.clone();
+        match download_audio(fetcher, entry, data_dir).await {
+            Ok(true) => report.downloaded.push(word),
+            Ok(false) => report.skipped.push(word),
+            Err(err) => match audio_link(entry) {
+                Some(link) => {
+                    error!("broken.link: {} -> {}", link, err.message);
+                    report.broken.push((link, err.message));
+                }
+                None => report.no_audio.push(word),
+            },
+        }
+    }
+    report
+}
+
+/// Downloads the audio of every stored entry of the dictionary and saves
+/// the entries whose `file` changed.
+pub async fn download_library(
+    fetcher: &dyn Fetcher,
+    data_dir: &str,
+    dictionary: &Dictionary,
+) -> ServiceExuctionResult<AudioReport> {
+    let json_dir = [data_dir, &dictionary.name(), JSON_DIR].join("/");
+    let mut report = AudioReport::default();
+    for json_file in json_files(&json_dir)? {
+        let mut entries = match read_entry(&json_file) {
+            Ok(entry) => vec![entry],
+            Err(err) => {
+                error!("invalid.entry: {} -> {}", json_file, err.message);
+                continue;
+            }
+        };
+        let before = entries[0].file.clone();
+        let entry_report = download_all(fetcher, &mut entries, data_dir).await;
+        if entries[0].file != before {
+            save(&json_file, &entries[0]).map_err(|err| ServiceError {
+                message: err.message,
+                error_type: ServiceErrorType::IOError,
+            })?;
+        }
+        report.downloaded.extend(entry_report.downloaded);
+        report.skipped.extend(entry_report.skipped);
+        report.no_audio.extend(entry_report.no_audio);
+        report.broken.extend(entry_report.broken);
+    }
+    info!("{}: {}", json_dir, report.summary());
+    Ok(report)
+}
+
+fn read_entry(json_file: &str) -> ServiceExuctionResult<DictionaryEntry> {
+    let content = fs::read_to_string(json_file)?;
+    serde_json::from_str(&content).map_err(|err| ServiceError {
+        message: format!("invalid.json: {}", err),
+        error_type: ServiceErrorType::SerializationError,
+    })
+}
+
+fn json_files(dir: &str) -> ServiceExuctionResult<Vec<String>> {
+    if !Path::new(dir).is_dir() {
+        return Err(not_found(&format!("dir.not.found: {}", dir)));
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with(JSON_EXT) {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod audio_tests {
+    use std::fs;
+
+    use common_libs::files::{file_name, save, temp_dir};
+
+    use crate::{
+        cache::key,
+        fixtures::{FixtureFetcher, BINARY_EXT, NOT_FOUND_EXT},
+        model::{Dictionary, DictionaryEntry, Pronunciation},
+    };
+
+    use super::{download_all, download_library, is_mp3, mp3_file};
+
+    const MP3: &[u8] = &[0xFF, 0xFB, 0x50, 0xC0, 0x00];
+
+    fn entry(word: &str, mp3_link: Option<&str>) -> DictionaryEntry {
+        DictionaryEntry {
+            uk: mp3_link.map(|link| Pronunciation {
+                ipa: None,
+                mp3_link: Some(link.to_string()),
+            }),
+            ..DictionaryEntry::new(Dictionary::Collins, word)
+        }
+    }
+
+    #[test]
+    fn is_mp3_test() {
+        assert!(is_mp3(MP3));
+        assert!(is_mp3(b"ID3\x04\x00"));
+        assert!(!is_mp3(b"<html>not found</html>"));
+        assert!(!is_mp3(&[0xFF]));
+        assert!(!is_mp3(&[]));
+    }
+
+    #[tokio::test]
+    async fn download_all_test() {
+        let fixtures_dir = temp_dir("mk_scraper_audio_fixtures");
+        let data_dir = temp_dir("mk_scraper_audio_data");
+        let link = |name: &str| format!("https://www.collinsdictionary.com/sounds/{}.mp3", name);
+        fs::write(file_name(fixtures_dir.as_str(), key(&link("rampage")).as_str(), BINARY_EXT), MP3).unwrap();
+        fs::write(file_name(fixtures_dir.as_str(), key(&link("html")).as_str(), BINARY_EXT), "<html>").unwrap();
+        fs::write(file_name(fixtures_dir.as_str(), key(&link("gone")).as_str(), NOT_FOUND_EXT), "").unwrap();
+        let fixtures = FixtureFetcher::replay(&fixtures_dir);
+
+        let mut entries = vec![
+            entry("rampage", Some(&link("rampage"))),
+            entry("Wind up", Some(&link("html"))),
+            entry("gone", Some(&link("gone"))),
+            entry("silent", None),
+        ];
+        let report = download_all(&fixtures, &mut entries, &data_dir).await;
+        assert_eq!(vec!["rampage"], report.downloaded);
+        assert_eq!(vec!["silent"], report.no_audio);
+        assert_eq!(2, report.broken.len());
+        assert!(report.broken[0].1.starts_with("invalid.mp3"));
+        assert_eq!(
+            Some(format!("{}/collins/mp3/rampage.mp3", data_dir)),
+            entries[0].file
+        );
+        assert_eq!(MP3, fs::read(entries[0].file.as_ref().unwrap()).unwrap());
+        assert!(entries[1].file.is_none());
+        assert!(!std::path::Path::new(&mp3_file(&data_dir, &entries[1])).exists());
+
+        entries[0].file = None;
+        let again = download_all(&fixtures, &mut entries[..1], &data_dir).await;
+        assert_eq!(vec!["rampage"], again.skipped);
+        assert!(entries[0].file.is_some());
+    }
+
+    #[tokio::test]
+    async fn download_library_test() {
+        let fixtures_dir = temp_dir("mk_scraper_library_fixtures");
+        let data_dir = temp_dir("mk_scraper_library_data");
+        let link = "https://www.collinsdictionary.com/sounds/dust-up.mp3";
+        fs::write(file_name(fixtures_dir.as_str(), key(link).as_str(), BINARY_EXT), MP3).unwrap();
+        let json_dir = format!("{}/collins/json", data_dir);
+        fs::create_dir_all(&json_dir).unwrap();
+        let json_file = format!("{}/dust up.json", json_dir);
+        save(&json_file, &entry("dust up", Some(link))).unwrap();
+
+        let fixtures = FixtureFetcher::replay(&fixtures_dir);
+        let report = download_library(&fixtures, &data_dir, &Dictionary::Collins).await.unwrap();
+        assert_eq!(vec!["dust up"], report.downloaded);
+        let saved: DictionaryEntry = serde_json::from_str(&fs::read_to_string(&json_file).unwrap()).unwrap();
+        assert_eq!(Some(format!("{}/collins/mp3/dust up.mp3", data_dir)), saved.file);
+
+        assert!(download_library(&fixtures, &data_dir, &Dictionary::Oxford).await.is_err());
+    }
+}
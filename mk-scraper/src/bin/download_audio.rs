@@ -0,0 +1,39 @@
+use common_libs::configure_log4rs;
+use mk_scraper::{
+    audio::download_library, http_client, merger::MERGED_DICTIONARIES, migration::DATA_DIR, model::Dictionary,
+};
+
+/// Downloads the missing pronunciations of the stored words and records
+/// their `file`.
+///
+/// `download_audio [dictionary ...]`, the default is all three dictionaries.
+#[tokio::main]
+async fn main() {
+    configure_log4rs();
+    let mut dictionaries: Vec<String> = std::env::args().skip(1).collect();
+    if dictionaries.is_empty() {
+        dictionaries = MERGED_DICTIONARIES.iter().map(|name| name.to_string()).collect();
+    }
+
+    let mut failed = false;
+    for name in dictionaries {
+        let dictionary = Dictionary::from(name.clone());
+        match download_library(http_client::default_fetcher(), DATA_DIR, &dictionary).await {
+            Ok(report) => {
+                for (link, reason) in &report.broken {
+                    println!("BROKEN {} -> {}", link, reason);
+                }
+                println!("{}: {}", name, report.summary());
+                failed |= !report.broken.is_empty();
+            }
+            Err(err) => {
+                println!("{}: {}", name, err.message);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
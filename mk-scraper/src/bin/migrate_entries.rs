@@ -0,0 +1,40 @@
+use common_libs::configure_log4rs;
+use log::info;
+use mk_scraper::migration::{migrate_dir, DATA_DIR};
+
+/// Upgrades the stored word files to the current schema version.
+///
+/// `migrate_entries [--dry-run] [dir ...]`, the default dir is `data/en-en`.
+fn main() {
+    configure_log4rs();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let mut dirs: Vec<String> = args.into_iter().filter(|arg| !arg.starts_with("--")).collect();
+    if dirs.is_empty() {
+        dirs.push(DATA_DIR.to_string());
+    }
+
+    let mut failed = false;
+    for dir in dirs {
+        match migrate_dir(&dir, dry_run) {
+            Ok(report) => {
+                for file_name in &report.migrated {
+                    info!("{}: {}", if dry_run { "to.migrate" } else { "migrated" }, file_name);
+                }
+                for (file_name, reason) in &report.failed {
+                    println!("FAILED {} -> {}", file_name, reason);
+                }
+                println!("{}: {}", dir, report.summary());
+                failed |= !report.failed.is_empty();
+            }
+            Err(err) => {
+                println!("{}: {}", dir, err.message);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{fs, time::Duration};
+
+use chrono::{DateTime, Utc};
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::{file_name, from_file, save},
+};
+use log::{debug, error};
+use reqwest::header::{HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{HTML_EXT, JSON_EXT};
+
+pub const CACHE_DIR: &str = "download/cache";
+pub const CACHE_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub dir: String,
+    pub ttl: Duration,
+    /// Ignore cached pages and always go to the network (the cache is still refreshed).
+    pub force_refresh: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            dir: CACHE_DIR.to_string(),
+            ttl: Duration::from_secs(CACHE_TTL_SECS),
+            force_refresh: false,
+        }
+    }
+}
+
+/// Metadata stored next to every cached page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPage {
+    pub url: String,
+    pub fetched_on: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CachedPage {
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self.etag.as_ref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self.last_modified.as_ref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+/// HTML pages on disk keyed by URL: `<dir>/<key>.html` plus `<dir>/<key>.json`.
+#[derive(Debug, Clone)]
+pub struct HtmlCache {
+    config: CacheConfig,
+}
+
+impl HtmlCache {
+    pub fn new(config: CacheConfig) -> Self {
+        HtmlCache { config }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    pub fn lookup(&self, url: &str) -> Option<(CachedPage, String)> {
+        let key = key(url);
+        let page = from_file::<_, CachedPage>(self.path(&key, JSON_EXT))?;
+        if page.url != url {
+            return None;
+        }
+        let html = fs::read_to_string(self.path(&key, HTML_EXT)).ok()?;
+        Some((page, html))
+    }
+
+    pub fn is_fresh(&self, page: &CachedPage) -> bool {
+        if self.config.force_refresh {
+            return false;
+        }
+        match (Utc::now() - page.fetched_on).to_std() {
+            Ok(age) => age < self.config.ttl,
+            Err(_) => true,
+        }
+    }
+
+    pub fn store(
+        &self,
+        url: &str,
+        html: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> ServiceExuctionResult<CachedPage> {
+        let key = key(url);
+        fs::create_dir_all(&self.config.dir)?;
+        fs::write(self.path(&key, HTML_EXT), html)?;
+        let page = CachedPage {
+            url: url.to_string(),
+            fetched_on: Utc::now(),
+            etag,
+            last_modified,
+        };
+        self.save_metadata(&key, &page)?;
+        debug!("cached: {} -> {}", url, key);
+        Ok(page)
+    }
+
+    /// Marks a page as fetched now, e.g. after a `304 Not Modified`.
+    pub fn touch(&self, page: &CachedPage) {
+        let refreshed = CachedPage {
+            fetched_on: Utc::now(),
+            ..page.clone()
+        };
+        if let Err(err) = self.save_metadata(&key(&page.url), &refreshed) {
+            error!("failed.to.refresh.cache: {} -> {}", page.url, err.message);
+        }
+    }
+
+    fn save_metadata(&self, key: &str, page: &CachedPage) -> ServiceExuctionResult<()> {
+        let path = self.path(key, JSON_EXT);
+        save(&path, page).map_err(|err| ServiceError {
+            message: err.message,
+            error_type: ServiceErrorType::IOError,
+        })
+    }
+
+    fn path(&self, key: &str, ext: &str) -> String {
+        file_name(self.config.dir.as_str(), key, ext)
+    }
+}
+
+/// File-system friendly key: the URL without its scheme, anything but
+/// letters and digits replaced by `_`.
+pub fn key(url: &str) -> String {
+    let without_scheme = url.split("://").last().unwrap_or(url);
+    without_scheme
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use common_libs::files::temp_dir;
+
+    use super::{key, CacheConfig, HtmlCache};
+
+    fn test_cache(name: &str, ttl: Duration, force_refresh: bool) -> HtmlCache {
+        HtmlCache::new(CacheConfig {
+            dir: temp_dir(format!("mk_scraper_cache_tests/{}", name)),
+            ttl,
+            force_refresh,
+        })
+    }
+
+    #[test]
+    fn key_test() {
+        assert_eq!(
+            "dictionary_cambridge_org_dictionary_english_wind_up",
+            key("https://dictionary.cambridge.org/dictionary/english/wind-up")
+        );
+        assert_eq!(
+            "www_oxfordlearnersdictionaries_com_definition_english_correct_2",
+            key("https://www.oxfordlearnersdictionaries.com/definition/english/correct_2/")
+        );
+    }
+
+    #[test]
+    fn store_and_lookup_test() {
+        let cache = test_cache("store", Duration::from_secs(60), false);
+        let url = "https://dictionary.cambridge.org/dictionary/english/correct";
+        assert!(cache.lookup(url).is_none());
+
+        let stored = cache
+            .store(url, "<html>correct</html>", Some("\"abc\"".to_string()), None)
+            .unwrap();
+        let (page, html) = cache.lookup(url).unwrap();
+        assert_eq!("<html>correct</html>", html);
+        assert_eq!(Some("\"abc\"".to_string()), page.etag);
+        assert!(cache.is_fresh(&page));
+        assert_eq!(1, stored.conditional_headers().len());
+    }
+
+    #[test]
+    fn ttl_and_force_refresh_test() {
+        let url = "https://www.collinsdictionary.com/dictionary/english/rampage";
+
+        let expired = test_cache("expired", Duration::from_secs(0), false);
+        let page = expired.store(url, "<html/>", None, None).unwrap();
+        assert!(!expired.is_fresh(&page));
+
+        let forced = test_cache("forced", Duration::from_secs(3600), true);
+        let page = forced.store(url, "<html/>", None, None).unwrap();
+        assert!(!forced.is_fresh(&page));
+
+        let old = super::CachedPage {
+            fetched_on: Utc::now() - chrono::Duration::hours(2),
+            ..page
+        };
+        let hourly = test_cache("hourly", Duration::from_secs(3600), false);
+        assert!(!hourly.is_fresh(&old));
+    }
+}
@@ -1,109 +1,104 @@
-use common_libs::files::file_name;
 use log::error;
 
-
-
-use crate::constants::HTML_EXT;
-
-use super::{
-    description, download_from_url, elements, first_element, mp3_element, mp3_element_to_url,
-    to_url, wget, DictionaryEntry,
+use crate::{
+    dictionary_scraper::{DictionaryScraper, PronunciationQueries, SenseQueries},
+    http_client::{self, Fetcher},
+    model::Dictionary,
+    DictionaryEntry,
 };
+
 const BASE_URL: &str = r#"https://dictionary.cambridge.org"#;
 const URL: &str = r#"https://dictionary.cambridge.org/dictionary/english/"#;
 pub const MP3_QUERY: &str = r#"source[type="audio/mpeg"]"#;
 pub const MP3_START_WITH: &str = r#"/media"#;
 pub const DEFINITION_QUERY: &str = r#"div[class="def ddef_d db"]"#;
+pub const HEADWORD_QUERIES: [&str; 2] = [
+    r#"div[class="di-title"] h2[class="headword tw-bw dhw dpos-h_hw "] b"#,
+    r#"div[class="di-title"] h2[class="headword tw-bw dhw dpos-h_hw "] span"#,
+];
 
-fn mp3_url(html: &str) -> Option<String> {
-    let element = mp3_element(MP3_QUERY, &html);
+pub struct CambridgeScraper;
 
-    if element.is_none() {
-        return None;
+impl DictionaryScraper for CambridgeScraper {
+    fn name(&self) -> &str {
+        "cambridge"
     }
 
-    let url = mp3_element_to_url(&element.unwrap(), MP3_START_WITH);
+    fn dictionary(&self) -> Dictionary {
+        Dictionary::Cambridge
+    }
 
-    if url.is_none() {
-        return None;
+    fn base_url(&self) -> &str {
+        URL
     }
 
-    let mut mp3_url = String::from(BASE_URL);
-    mp3_url.push_str(&url.unwrap());
-    Some(mp3_url)
-}
+    fn definition_query(&self) -> &str {
+        DEFINITION_QUERY
+    }
 
-fn to_definitions(html: &str) -> Vec<String> {
-    let definitions = elements(DEFINITION_QUERY, &html, true);
-    definitions
-        .into_iter()
-        .map(|e| description(e))
-        .filter(Option::is_some)
-        .map(Option::unwrap)
-        .collect()
-}
+    fn mp3_query(&self) -> &str {
+        MP3_QUERY
+    }
 
-pub async fn scrape(word: &str) -> Option<DictionaryEntry> {
-    if word.trim().is_empty() || word.trim().len() < 3 {
-        error!("word.min.lenght.is.3");
-        return None;
-    }
-    let url = to_url(URL, word);
-    let html = match download_from_url(&url).await {
-        Ok(content) => content,
-        Err(_err) => {
-            error!("failed.to.read.from.url: {}", url);
-            let file_name = file_name("download/tmp/cambridge", word, HTML_EXT);
-            match wget(&url, &file_name) {
-                Ok(html) => html,
-                Err(_err) => {
-                    error!("resource.not.found: {}", url);
-                    return None;
-                }
-            }
-        }
-    };
+    fn mp3_start_with(&self) -> &str {
+        MP3_START_WITH
+    }
 
-    let mp3_link = mp3_url(&html);
-    let definitions = to_definitions(&html);
+    fn headword_queries(&self) -> Vec<&str> {
+        HEADWORD_QUERIES.to_vec()
+    }
 
-    if mp3_link.is_none() || definitions.is_empty() {
-        return None;
+    fn browser_fallback(&self) -> bool {
+        true
     }
 
-    let mut found = first_element(
-        r#"div[class="di-title"] h2[class="headword tw-bw dhw dpos-h_hw "] b"#,
-        &html,
-        true,
-    );
+    /// Cambridge links its audio relative to the site root.
+    fn mp3_base_url(&self) -> Option<&str> {
+        Some(BASE_URL)
+    }
 
-    if found.is_none() {
-        found = first_element(
-            r#"div[class="di-title"] h2[class="headword tw-bw dhw dpos-h_hw "] span"#,
-            &html,
-            true,
-        );
+    fn sense_queries(&self) -> Option<SenseQueries> {
+        Some(SenseQueries {
+            block: Some(r#"div.entry-body__el"#.to_string()),
+            part_of_speech: Some(r#"div.posgram span.pos"#.to_string()),
+            sense: r#"div.def-block"#.to_string(),
+            definition: r#"div.ddef_d"#.to_string(),
+            grammar: Some(r#"span.gram"#.to_string()),
+            examples: Some(r#"div.examp span.eg"#.to_string()),
+            cefr: Some(r#"span.epp-xref"#.to_string()),
+            domains: Some(r#"span.domain"#.to_string()),
+            exclude: None,
+            regions: vec![],
+        })
     }
 
+    fn pronunciation_queries(&self) -> PronunciationQueries {
+        PronunciationQueries {
+            uk_ipa: Some(r#"span.uk span.ipa"#.to_string()),
+            uk_mp3: Some(r#"span.uk source[type="audio/mpeg"]"#.to_string()),
+            us_ipa: Some(r#"span.us span.ipa"#.to_string()),
+            us_mp3: Some(r#"span.us source[type="audio/mpeg"]"#.to_string()),
+        }
+    }
+}
 
-    let word = match found {
-        Some(v) => v,
-        None => word.to_lowercase()
-    };
+pub async fn scrape(word: &str) -> Option<DictionaryEntry> {
+    scrape_with(http_client::default_fetcher(), word).await
+}
 
-    Some(DictionaryEntry {
-        source: super::Dictionary::Cambridge,
-        word: word.clone().to_lowercase(),
-        url: to_url(URL, &word),
-        mp3_link,
-        definitions,
-        file: None,
-    })
+pub async fn scrape_with(fetcher: &dyn Fetcher, word: &str) -> Option<DictionaryEntry> {
+    match CambridgeScraper.scrape(fetcher, word).await {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            error!("resource.not.found: {} -> {}", word, err.message);
+            None
+        }
+    }
 }
 
 #[cfg(test)]
 mod cambridge_unit_tests {
-    use crate::cambridge_scraper;
+    use crate::{cambridge_scraper, fixtures::{FixtureFetcher, FIXTURES_DIR}};
 
     
     #[tokio::test]
@@ -117,6 +112,7 @@ mod cambridge_unit_tests {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn cambridge_fail_test() {
         let res = cambridge_scraper::scrape(r#"undertake"#).await;
         assert!(res.is_some());
@@ -127,6 +123,7 @@ mod cambridge_unit_tests {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn cambridge_scraper_test() {
         let words = vec![r#"wind up"#, 
             r#"bail out"#,
@@ -141,4 +138,28 @@ mod cambridge_unit_tests {
         }    
         
     }
+
+    #[tokio::test]
+    async fn cambridge_replay_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let res = cambridge_scraper::scrape_with(&fixtures, "correct").await;
+        assert!(res.is_some());
+        let entry = res.unwrap();
+        assert_eq!("correct", entry.word);
+        assert_eq!(9, entry.definitions.len());
+        assert!(entry.mp3_link.clone().unwrap().ends_with(".mp3"));
+
+        assert_eq!(9, entry.senses.len());
+        assert_eq!(Some("adjective".to_string()), entry.senses[0].part_of_speech);
+        assert_eq!(Some("A2".to_string()), entry.senses[0].cefr);
+        assert_eq!(4, entry.senses[0].examples.len());
+        assert_eq!(Some("[ T ]".to_string()), entry.senses[8].grammar);
+        assert_eq!(vec!["adjective", "verb"], entry.parts_of_speech());
+        let uk = entry.uk.unwrap();
+        assert_eq!(Some("kəˈrekt".to_string()), uk.ipa);
+        assert!(uk.mp3_link.unwrap().contains("/uk_pron/"));
+        assert!(entry.us.unwrap().mp3_link.unwrap().contains("/us_pron/"));
+
+        assert!(cambridge_scraper::scrape_with(&fixtures, "not recorded").await.is_none());
+    }
 }
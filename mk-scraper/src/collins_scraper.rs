@@ -1,9 +1,9 @@
-use common_libs::files::file_name;
 use log::error;
-use crate::{model::DictionaryEntry, constants::HTML_EXT};
 
-use super::{
-    description, elements, mp3_element, mp3_element_to_url, to_url, wget, download_from_url,
+use crate::{
+    dictionary_scraper::{DictionaryScraper, PhrasalVerbQueries, PronunciationQueries, RegionQuery, SenseQueries},
+    http_client::{self, Fetcher},
+    model::{Dictionary, DictionaryEntry},
 };
 
 const URL: &str = r#"https://www.collinsdictionary.com/dictionary/english/"#;
@@ -11,66 +11,112 @@ pub const MP3_QUERY: &str =
     r#"a[class="hwd_sound sound audio_play_button icon-volume-up ptr"]"#;
 pub const MP3_START_WITH: &str = r#"https:"#;
 pub const DEFINITION_QUERY: &str = r#"div[class="sense"] div[class="def"]"#;
+pub const HEADWORD_QUERY: &str = r#"div.dictentry h2.h2_entry span.orth"#;
+/// Phrasal verbs without a page of their own are run on after the verb.
+pub const PHRASAL_VERB_QUERY: &str = r#"div.type-phrasalverb"#;
+pub const PHRASAL_VERB_HEADWORD_QUERY: &str = r#"span.orth"#;
 
-fn mp3_url(html: &str) -> Option<String> {
-    let element = mp3_element(MP3_QUERY, &html);
+pub struct CollinsScraper;
 
-    if element.is_none() {
-        return None;
+impl DictionaryScraper for CollinsScraper {
+    fn name(&self) -> &str {
+        "collins"
     }
 
-    mp3_element_to_url(&element.unwrap(), MP3_START_WITH)
+    fn dictionary(&self) -> Dictionary {
+        Dictionary::Collins
+    }
+
+    fn base_url(&self) -> &str {
+        URL
+    }
+
+    fn definition_query(&self) -> &str {
+        DEFINITION_QUERY
+    }
+
+    fn mp3_query(&self) -> &str {
+        MP3_QUERY
+    }
+
+    fn mp3_start_with(&self) -> &str {
+        MP3_START_WITH
+    }
+
+    fn headword_queries(&self) -> Vec<&str> {
+        vec![HEADWORD_QUERY]
+    }
+
+    fn browser_fallback(&self) -> bool {
+        true
+    }
+
+    fn sense_queries(&self) -> Option<SenseQueries> {
+        Some(SenseQueries {
+            block: None,
+            part_of_speech: Some(r#"span.pos"#.to_string()),
+            sense: r#"div.hom"#.to_string(),
+            definition: r#"div.def"#.to_string(),
+            grammar: Some(r#"span.subc"#.to_string()),
+            examples: Some(r#"div.type-example span.quote"#.to_string()),
+            cefr: None,
+            domains: Some(r#"span.type-subj"#.to_string()),
+            exclude: Some(r#"div.re"#.to_string()),
+            regions: vec![
+                region(r#"div.Cob_Adv_Brit"#, "British"),
+                region(r#"div.Collins_Eng_Dict"#, "British"),
+                region(r#"div.Large_US_Webster"#, "American"),
+                region(r#"div.Penguin"#, "American"),
+            ],
+        })
+    }
+
+    fn pronunciation_queries(&self) -> PronunciationQueries {
+        PronunciationQueries {
+            uk_ipa: Some(r#"div.Collins_Eng_Dict span.pron"#.to_string()),
+            uk_mp3: Some(r#"div.Collins_Eng_Dict a.hwd_sound"#.to_string()),
+            us_ipa: Some(r#"div.Large_US_Webster span.pron"#.to_string()),
+            us_mp3: Some(r#"div.Large_US_Webster a.hwd_sound"#.to_string()),
+        }
+    }
+
+    fn phrasal_verb_queries(&self) -> Option<PhrasalVerbQueries> {
+        Some(PhrasalVerbQueries {
+            block: PHRASAL_VERB_QUERY.to_string(),
+            headword: PHRASAL_VERB_HEADWORD_QUERY.to_string(),
+        })
+    }
 }
 
-fn to_definitions(html: &str) -> Vec<String> {
-    let definitions = elements(DEFINITION_QUERY, &html, true);
-    definitions
-        .into_iter()
-        .map(|e| description(e))
-        .filter(Option::is_some)
-        .map(Option::unwrap)
-        .collect()
+fn region(block: &str, region: &str) -> RegionQuery {
+    RegionQuery {
+        block: block.to_string(),
+        region: region.to_string(),
+    }
 }
 
 pub async fn scrape(word: &str) -> Option<DictionaryEntry> {
-    if word.trim().is_empty() || word.trim().len() < 3 {
-        error!("word.min.lenght.is.3");
-        return None;
-    }
-
-    let url = to_url(URL, word);
-    let html = match download_from_url(&url).await {
-        Ok(content) => content,
-        Err(_err) => {
-            error!("failed.to.read.from.url: {}", url);
-            let file_name = file_name("download/tmp/collins", word, HTML_EXT);
-            match wget(&url, &file_name) {
-                Ok(html) => html,
-                Err(_err) => {
-                    error!("resource.not.found: {}", url);
-                    return None
-                }
-            }
+    scrape_with(http_client::default_fetcher(), word).await
+}
+
+pub async fn scrape_with(fetcher: &dyn Fetcher, word: &str) -> Option<DictionaryEntry> {
+    match CollinsScraper.scrape(fetcher, word).await {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            error!("resource.not.found: {} -> {}", word, err.message);
+            None
         }
-    };
-    let mp3_link = mp3_url(&html);
-    let definitions = to_definitions(&html);
-
-    Some(DictionaryEntry {
-        source: super::Dictionary::Cambridge,
-        word: word.clone().to_lowercase(),
-        url: to_url(URL, word),
-        mp3_link,
-        definitions,
-        file: None,
-    })
+    }
 }
 #[cfg(test)]
 mod collins_unit_tests{
-    use crate::collins_scraper;
+    use common_libs::files::temp_dir;
+
+    use crate::{cache::key, collins_scraper, fixtures::{FixtureFetcher, FIXTURES_DIR}, model::Dictionary, registry::Registry};
 
     
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn scrape_test() {
         let html = collins_scraper::scrape("dust up").await;
         assert!(html.is_some());
@@ -78,6 +124,7 @@ mod collins_unit_tests{
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn collins_scraper_test() {
         let res = collins_scraper::scrape(r#"correct"#).await;
         assert!(res.is_some());
@@ -87,4 +134,107 @@ mod collins_unit_tests{
         );
     }
 
+    #[tokio::test]
+    async fn collins_replay_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let res = collins_scraper::scrape_with(&fixtures, "rampage").await;
+        assert!(res.is_some());
+        let entry = res.unwrap();
+        assert!(!entry.definitions.is_empty());
+        assert!(entry.mp3_link.is_some());
+        assert_eq!("rampage", entry.word);
+        assert!(matches!(entry.source, Dictionary::Collins));
+
+        assert_eq!(Some("verb".to_string()), entry.senses[0].part_of_speech);
+        assert_eq!(Some("British".to_string()), entry.senses[0].region);
+        assert!(entry.senses.iter().any(|sense| sense.region == Some("American".to_string())));
+        assert_eq!(2, entry.senses[0].examples.len());
+        assert_eq!(Some("(intransitive)".to_string()), entry.senses[1].grammar);
+        assert_eq!(Some("ræmˈpeɪdʒ".to_string()), entry.uk.unwrap().ipa);
+        assert_eq!(Some("ˈræmˌpeɪdʒ".to_string()), entry.us.unwrap().ipa);
+    }
+
+    const DUST: &str = r#"<html><body>
+<div class="dictionary Cob_Adv_Brit dictentry"><h2 class="h2_entry"><span class="orth">dust</span></h2>
+<a class="hwd_sound sound audio_play_button icon-volume-up ptr" data-src-mp3="https://www.collinsdictionary.com/sounds/hwd_sounds/dust.mp3"></a>
+<div class="hom"><span class="gramGrp pos">noun</span><div class="sense"><div class="def">Dust is very small dry particles of earth or sand.</div></div></div>
+<div class="re type-phrasalverb"><span class="orth">dust off</span>
+<div class="hom"><span class="gramGrp pos">phrasal verb</span><div class="sense"><div class="def">If you dust something off, you start using it again.</div></div></div></div>
+<div class="re type-phrasalverb"><span class="orth">dust <b>up</b></span>
+<a class="hwd_sound sound audio_play_button icon-volume-up ptr" data-src-mp3="https://www.collinsdictionary.com/sounds/hwd_sounds/dust-up.mp3"></a>
+<div class="hom"><div class="sense"><div class="def">To dust someone up is to attack them.</div></div></div></div>
+</div></body></html>"#;
+
+    fn fixture_dir(name: &str, pages: &[(&str, &str)]) -> String {
+        let dir = temp_dir(format!("mk_scraper_collins_tests/{}", name));
+        for (url, html) in pages {
+            let ext = if html.is_empty() { "notfound" } else { "html" };
+            std::fs::write(format!("{}/{}.{}", dir, key(url), ext), html).unwrap();
+        }
+        dir
+    }
+
+    #[tokio::test]
+    async fn collins_not_found_test() {
+        let dir = fixture_dir(
+            "not_found",
+            &[
+                (
+                    "https://www.collinsdictionary.com/dictionary/english/qwertyuiop",
+                    "<html><body><h1>Sorry, no results for “qwertyuiop” in the English Dictionary.</h1></body></html>",
+                ),
+                (
+                    "https://www.collinsdictionary.com/dictionary/english/empty",
+                    r#"<html><body><div class="dictionary Cob_Adv_Brit dictentry"><h2 class="h2_entry"><span class="orth">empty</span></h2></div></body></html>"#,
+                ),
+            ],
+        );
+        let fixtures = FixtureFetcher::replay(dir);
+        assert!(collins_scraper::scrape_with(&fixtures, "qwertyuiop").await.is_none());
+        assert!(collins_scraper::scrape_with(&fixtures, "empty").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn phrasal_verb_test() {
+        let dir = fixture_dir(
+            "phrasal_verb",
+            &[
+                ("https://www.collinsdictionary.com/dictionary/english/dust-up", ""),
+                ("https://www.collinsdictionary.com/dictionary/english/dust", DUST),
+            ],
+        );
+        let fixtures = FixtureFetcher::replay(dir);
+
+        let dust = collins_scraper::scrape_with(&fixtures, "dust").await.unwrap();
+        assert_eq!(vec!["noun"], dust.parts_of_speech());
+        assert_eq!(1, dust.senses.len());
+
+        let dust_up = collins_scraper::scrape_with(&fixtures, "Dust up").await.unwrap();
+        assert_eq!("dust up", dust_up.word);
+        assert_eq!(vec!["To dust someone up is to attack them.".to_string()], dust_up.definitions);
+        assert_eq!(vec!["phrasal verb"], dust_up.parts_of_speech());
+        assert!(dust_up.mp3_link.unwrap().ends_with("dust-up.mp3"));
+
+        assert!(collins_scraper::scrape_with(&fixtures, "dust down").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn configured_phrasal_verb_test() {
+        let dir = fixture_dir(
+            "configured_phrasal_verb",
+            &[
+                ("https://www.collinsdictionary.com/dictionary/english/dust-up", ""),
+                ("https://www.collinsdictionary.com/dictionary/english/dust", DUST),
+            ],
+        );
+        let fixtures = FixtureFetcher::replay(dir);
+        let mut registry = Registry::default();
+        registry.load_config("../config/scrapers.yml").unwrap();
+
+        let collins = registry.lookup("collins").unwrap();
+        let dust_up = collins.scrape(&fixtures, "dust up").await.unwrap();
+        assert_eq!("dust up", dust_up.word);
+        assert_eq!(vec!["phrasal verb"], dust_up.parts_of_speech());
+        assert!(dust_up.mp3_link.unwrap().ends_with("dust-up.mp3"));
+    }
 }
@@ -0,0 +1,577 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+use async_trait::async_trait;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::file_name,
+};
+use log::{error, info};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    check_not_found_with,
+    constants::{HTML_EXT, NOT_FOUND},
+    description, elements, first_element,
+    http_client::Fetcher,
+    model::{Dictionary, DictionaryEntry, Pronunciation, Sense, SCHEMA_VERSION},
+    mp3_element, mp3_element_to_url, save_as_browser, to_url,
+};
+
+pub const TMP_DIR: &str = "download/tmp";
+pub const MIN_WORD_LENGTH: usize = 3;
+/// Upper bound of the numbered pages (`<word>_1`, `<word>_2`, ...) followed for one word.
+pub const MAX_SENSE_PAGES: usize = 10;
+/// Part of speech of the senses found through `phrasal_verb_queries`.
+pub const PHRASAL_VERB: &str = "phrasal verb";
+
+lazy_static! {
+    static ref TMP: RwLock<String> = RwLock::new(TMP_DIR.to_string());
+}
+
+/// CSS selectors of the structured parts of a word page. `sense` is looked up
+/// inside every `block` (the whole page when unset) and the other selectors
+/// inside every sense; `part_of_speech` falls back to the sense's block.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SenseQueries {
+    #[serde(default)]
+    pub block: Option<String>,
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    pub sense: String,
+    pub definition: String,
+    #[serde(default)]
+    pub grammar: Option<String>,
+    #[serde(default)]
+    pub examples: Option<String>,
+    /// A selector, or `@name` to read the `name` attribute of the sense element.
+    #[serde(default)]
+    pub cefr: Option<String>,
+    #[serde(default)]
+    pub domains: Option<String>,
+    /// Senses inside these elements are skipped, e.g. derived words run on after the entry.
+    #[serde(default)]
+    pub exclude: Option<String>,
+    /// Sections of the page for one variety of English; their senses get its `region`.
+    #[serde(default)]
+    pub regions: Vec<RegionQuery>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionQuery {
+    pub block: String,
+    /// e.g. `British`, `American`
+    pub region: String,
+}
+
+impl SenseQueries {
+    pub fn selectors(&self) -> Vec<&str> {
+        let mut selectors = vec![self.sense.as_str(), self.definition.as_str()];
+        let optional = [
+            &self.block,
+            &self.part_of_speech,
+            &self.grammar,
+            &self.examples,
+            &self.cefr,
+            &self.domains,
+            &self.exclude,
+        ];
+        selectors.extend(
+            optional
+                .into_iter()
+                .filter_map(|query| query.as_deref())
+                .filter(|query| !query.starts_with('@')),
+        );
+        selectors.extend(self.regions.iter().map(|region| region.block.as_str()));
+        selectors
+    }
+}
+
+/// Where a phrasal verb without a page of its own is run on after its verb,
+/// e.g. `dust up` on the `dust` page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhrasalVerbQueries {
+    /// The element holding one phrasal verb, its senses and audio.
+    pub block: String,
+    /// The phrasal verb's headword inside the block.
+    pub headword: String,
+}
+
+impl PhrasalVerbQueries {
+    pub fn selectors(&self) -> Vec<&str> {
+        vec![self.block.as_str(), self.headword.as_str()]
+    }
+}
+
+/// Selectors of the IPA text and of the element holding the audio link, per accent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PronunciationQueries {
+    #[serde(default)]
+    pub uk_ipa: Option<String>,
+    #[serde(default)]
+    pub uk_mp3: Option<String>,
+    #[serde(default)]
+    pub us_ipa: Option<String>,
+    #[serde(default)]
+    pub us_mp3: Option<String>,
+}
+
+impl PronunciationQueries {
+    pub fn selectors(&self) -> Vec<&str> {
+        [&self.uk_ipa, &self.uk_mp3, &self.us_ipa, &self.us_mp3]
+            .into_iter()
+            .filter_map(|query| query.as_deref())
+            .collect()
+    }
+}
+
+/// A dictionary website the words can be scraped from. Implementors describe
+/// where the pages are and how to read them; the provided `scrape` ties it
+/// together and can be overridden for sources with unusual page layouts.
+#[async_trait]
+pub trait DictionaryScraper: Send + Sync {
+    /// Registry name, lower case (e.g. `cambridge`).
+    fn name(&self) -> &str;
+
+    fn dictionary(&self) -> Dictionary;
+
+    /// Word pages are found at `base_url` + the hyphenated word.
+    fn base_url(&self) -> &str;
+
+    fn definition_query(&self) -> &str;
+
+    fn mp3_query(&self) -> &str;
+
+    /// The part of the mp3 element where the link starts.
+    fn mp3_start_with(&self) -> &str;
+
+    fn headword_queries(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    /// Retry through the browser-like client when the plain one fails.
+    fn browser_fallback(&self) -> bool {
+        false
+    }
+
+    /// Keep pages that have no definitions or no pronunciation.
+    fn keep_incomplete(&self) -> bool {
+        false
+    }
+
+    /// Words with several entries are split over `<word>_1`, `<word>_2`, ...
+    fn numbered_senses(&self) -> bool {
+        false
+    }
+
+    /// Phrases (lower case) that mark a page as "word not found".
+    fn not_found_markers(&self) -> Vec<&str> {
+        NOT_FOUND.to_vec()
+    }
+
+    /// Prepended to the pronunciation links when the site links them relatively.
+    fn mp3_base_url(&self) -> Option<&str> {
+        None
+    }
+
+    fn sense_queries(&self) -> Option<SenseQueries> {
+        None
+    }
+
+    fn pronunciation_queries(&self) -> PronunciationQueries {
+        PronunciationQueries::default()
+    }
+
+    /// When set, a phrasal verb that has no page is looked up on the page of its verb.
+    fn phrasal_verb_queries(&self) -> Option<PhrasalVerbQueries> {
+        None
+    }
+
+    fn url(&self, word: &str) -> String {
+        to_url(self.base_url(), word)
+    }
+
+    fn headword(&self, html: &str) -> Option<String> {
+        self.headword_queries()
+            .into_iter()
+            .find_map(|query| first_element(query, html, true))
+            .map(|word| word.trim().to_lowercase())
+    }
+
+    fn mp3_url(&self, html: &str) -> Option<String> {
+        let element = mp3_element(self.mp3_query(), html)?;
+        self.mp3_element_url(&element)
+    }
+
+    fn mp3_element_url(&self, element: &str) -> Option<String> {
+        let url = mp3_element_to_url(element, self.mp3_start_with())?;
+        match self.mp3_base_url() {
+            Some(base_url) => Some([base_url, &url].join("")),
+            None => Some(url),
+        }
+    }
+
+    /// Senses with their part of speech, examples and labels; just the
+    /// definitions when the scraper has no `sense_queries`.
+    fn senses(&self, html: &str) -> Vec<Sense> {
+        match self.sense_queries() {
+            Some(queries) => parse_senses(&queries, html),
+            None => self.definitions(html).into_iter().map(Sense::from).collect(),
+        }
+    }
+
+    fn pronunciation(&self, html: &str, ipa_query: Option<&str>, mp3_query: Option<&str>) -> Option<Pronunciation> {
+        let ipa = ipa_query
+            .and_then(|query| first_text(query, html))
+            .map(|ipa| ipa.trim_matches(|c: char| "/()[], ".contains(c)).to_string())
+            .filter(|ipa| !ipa.is_empty());
+        let mp3_link = mp3_query
+            .and_then(|query| mp3_element(query, html))
+            .and_then(|element| self.mp3_element_url(&element));
+
+        if ipa.is_none() && mp3_link.is_none() {
+            return None;
+        }
+        Some(Pronunciation { ipa, mp3_link })
+    }
+
+    fn definitions(&self, html: &str) -> Vec<String> {
+        elements(self.definition_query(), html, true)
+            .into_iter()
+            .filter_map(description)
+            .collect()
+    }
+
+    /// True when the site answered with another page than the word's own,
+    /// e.g. Oxford sending `correct` to `correct_1`.
+    fn redirected(&self, word: &str, html: &str) -> bool {
+        match first_element(r#"link[rel="canonical"]"#, html, false) {
+            Some(canonical) => !canonical.contains(&[r#"""#, &self.url(word), r#"""#].join("")),
+            None => false,
+        }
+    }
+
+    /// The number of the page the site redirected to, e.g. 1 for `correct_1`.
+    fn sense_page(&self, word: &str, html: &str) -> Option<usize> {
+        let canonical = first_element(r#"link[rel="canonical"]"#, html, false)?;
+        let prefix = [self.url(word).as_str(), "_"].join("");
+        let start = canonical.find(&prefix)? + prefix.len();
+        canonical[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    async fn download_page(&self, fetcher: &dyn Fetcher, url: &str) -> ServiceExuctionResult<String> {
+        let html = fetcher.get_text(url).await?;
+        check_not_found_with(&html, &self.not_found_markers())?;
+        Ok(html)
+    }
+
+    async fn download(&self, fetcher: &dyn Fetcher, word: &str) -> ServiceExuctionResult<String> {
+        let url = self.url(word);
+        match self.download_page(fetcher, &url).await {
+            Ok(html) => Ok(html),
+            Err(err) if !self.browser_fallback() => Err(err),
+            Err(_) => {
+                error!("failed.to.read.from.url: {}", url);
+                let tmp_dir = [tmp_dir(), self.name().to_string()].join("/");
+                let file_name = file_name(tmp_dir.as_str(), word, HTML_EXT);
+                let html = save_as_browser(fetcher, &url, &file_name).await?;
+                check_not_found_with(&html, &self.not_found_markers())?;
+                Ok(html)
+            }
+        }
+    }
+
+    /// Builds the entry from a downloaded page; fails when the page has no
+    /// definitions or no pronunciation, unless `keep_incomplete` is set.
+    fn parse(&self, word: &str, html: &str) -> ServiceExuctionResult<DictionaryEntry> {
+        let mp3_link = self.mp3_url(html);
+        let definitions = self.definitions(html);
+
+        if !self.keep_incomplete() && (mp3_link.is_none() || definitions.is_empty()) {
+            return Err(not_found("definitions or/and mp3 files are not found"));
+        }
+
+        let word = self.headword(html).unwrap_or_else(|| word.to_lowercase());
+        let queries = self.pronunciation_queries();
+        Ok(DictionaryEntry {
+            schema_version: SCHEMA_VERSION,
+            source: self.dictionary(),
+            url: self.url(&word),
+            word,
+            mp3_link,
+            definitions,
+            file: None,
+            senses: self.senses(html),
+            uk: self.pronunciation(html, queries.uk_ipa.as_deref(), queries.uk_mp3.as_deref()),
+            us: self.pronunciation(html, queries.us_ipa.as_deref(), queries.us_mp3.as_deref()),
+        })
+    }
+
+    /// Follows the numbered pages of a word (e.g. the verb and the noun of a
+    /// homograph) until one is not found and merges their senses, each with
+    /// the part of speech of its own page. The page the site redirected to
+    /// is taken from `html` rather than downloaded again. A page that fails
+    /// otherwise (a timeout, a 5xx) ends the merge with the pages so far.
+    async fn merge_senses(&self, fetcher: &dyn Fetcher, word: &str, html: &str) -> ServiceExuctionResult<DictionaryEntry> {
+        let mut merged: Option<DictionaryEntry> = None;
+        let first = match self.sense_page(word, html) {
+            Some(page) => {
+                self.merge_page(&mut merged, &[self.url(word), page.to_string()].join("_"), html);
+                page + 1
+            }
+            None => 1,
+        };
+        for page in first..=MAX_SENSE_PAGES {
+            let url = [self.url(word), page.to_string()].join("_");
+            let html = match self.download_page(fetcher, &url).await {
+                Ok(html) => html,
+                Err(err) if err.error_type == ServiceErrorType::ResourceNotFound => {
+                    info!("last.sense.page: {} -> {}", url, err.message);
+                    break;
+                }
+                Err(err) => {
+                    error!("sense.page.failed: {} -> {}", url, err.message);
+                    break;
+                }
+            };
+            self.merge_page(&mut merged, &url, &html);
+        }
+        merged.ok_or_else(|| not_found("senses.not.found"))
+    }
+
+    fn merge_page(&self, merged: &mut Option<DictionaryEntry>, url: &str, html: &str) {
+        match (self.parse(url.rsplit('/').next().unwrap_or_default(), html), merged.as_mut()) {
+            (Ok(sense), None) => *merged = Some(sense),
+            (Ok(sense), Some(entry)) => entry.append(sense),
+            (Err(err), _) => error!("sense.page.skipped: {} -> {}", url, err.message),
+        }
+    }
+
+    /// Builds the entry from the phrasal verb's block on the verb page.
+    fn parse_phrasal_verb(
+        &self,
+        queries: &PhrasalVerbQueries,
+        word: &str,
+        verb: &str,
+        html: &str,
+    ) -> ServiceExuctionResult<DictionaryEntry> {
+        let word = word.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let block = elements(&queries.block, html, false)
+            .into_iter()
+            .find(|block| {
+                first_text(&queries.headword, block).map(|headword| headword.to_lowercase()).as_deref()
+                    == Some(word.as_str())
+            })
+            .ok_or_else(|| not_found("phrasal.verb.not.found"))?;
+
+        let definitions = self.definitions(&block);
+        let mp3_link = self.mp3_url(&block).or_else(|| self.mp3_url(html));
+        if definitions.is_empty() || mp3_link.is_none() {
+            return Err(not_found("definitions or/and mp3 files are not found"));
+        }
+
+        let sense_queries = SenseQueries {
+            exclude: None,
+            ..self.sense_queries().unwrap_or_default()
+        };
+        let senses = parse_senses(&sense_queries, &block)
+            .into_iter()
+            .map(|mut sense| {
+                sense.part_of_speech = sense.part_of_speech.or_else(|| Some(PHRASAL_VERB.to_string()));
+                sense
+            })
+            .collect();
+        let pronunciations = self.pronunciation_queries();
+        Ok(DictionaryEntry {
+            schema_version: SCHEMA_VERSION,
+            source: self.dictionary(),
+            url: self.url(verb),
+            word,
+            mp3_link,
+            file: None,
+            definitions,
+            senses,
+            uk: self.pronunciation(html, pronunciations.uk_ipa.as_deref(), pronunciations.uk_mp3.as_deref()),
+            us: self.pronunciation(html, pronunciations.us_ipa.as_deref(), pronunciations.us_mp3.as_deref()),
+        })
+    }
+
+    async fn scrape(&self, fetcher: &dyn Fetcher, word: &str) -> ServiceExuctionResult<DictionaryEntry> {
+        validate_word(word)?;
+        let html = match (self.download(fetcher, word).await, self.phrasal_verb_queries()) {
+            (Ok(html), _) => html,
+            (Err(err), Some(queries))
+                if err.error_type == ServiceErrorType::ResourceNotFound && is_phrasal_verb(word) =>
+            {
+                let verb = word.split_whitespace().next().unwrap_or_default();
+                info!("phrasal.verb.page.not.found: {}. Trying {}", word, verb);
+                let html = self.download(fetcher, verb).await?;
+                return self.parse_phrasal_verb(&queries, word, verb, &html);
+            }
+            (Err(err), _) => return Err(err),
+        };
+        if self.numbered_senses() && self.redirected(word, &html) {
+            return self.merge_senses(fetcher, word, &html).await;
+        }
+        self.parse(word, &html)
+    }
+}
+
+/// Where pages downloaded through the browser fallback are kept, `TMP_DIR` by default.
+pub fn tmp_dir() -> String {
+    TMP.read().unwrap().clone()
+}
+
+pub fn set_tmp_dir(dir: &str) {
+    *TMP.write().unwrap() = dir.trim_end_matches('/').to_string();
+}
+
+pub fn validate_word(word: &str) -> ServiceExuctionResult<()> {
+    if word.trim().len() < MIN_WORD_LENGTH {
+        error!("word.min.lenght.is.3");
+        return Err(ServiceError {
+            message: "word.min.lenght.is.3".to_string(),
+            error_type: ServiceErrorType::Failure,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_senses(queries: &SenseQueries, html: &str) -> Vec<Sense> {
+    let (sense_selector, definition_selector) = match (selector(&queries.sense), selector(&queries.definition)) {
+        (Some(sense), Some(definition)) => (sense, definition),
+        _ => return vec![],
+    };
+    let pos_selector = queries.part_of_speech.as_deref().and_then(selector);
+    let grammar_selector = queries.grammar.as_deref().and_then(selector);
+    let examples_selector = queries.examples.as_deref().and_then(selector);
+    let domains_selector = queries.domains.as_deref().and_then(selector);
+
+    let document = Html::parse_document(html);
+    let blocks: Vec<ElementRef> = match queries.block.as_deref().and_then(selector) {
+        Some(block) => document.select(&block).collect(),
+        None => vec![document.root_element()],
+    };
+
+    let excluded: HashSet<_> = match queries.exclude.as_deref().and_then(selector) {
+        Some(exclude) => document
+            .select(&exclude)
+            .flat_map(|element| element.select(&sense_selector))
+            .map(|sense| sense.id())
+            .collect(),
+        None => HashSet::new(),
+    };
+    let mut regions = HashMap::new();
+    for query in &queries.regions {
+        if let Some(region_selector) = selector(&query.block) {
+            for block in document.select(&region_selector) {
+                for sense in block.select(&sense_selector) {
+                    regions.insert(sense.id(), query.region.clone());
+                }
+            }
+        }
+    }
+
+    let mut senses = vec![];
+    for block in blocks {
+        let block_pos = pos_selector.as_ref().and_then(|pos| first_text_in(block, pos));
+        for element in block.select(&sense_selector) {
+            if excluded.contains(&element.id()) {
+                continue;
+            }
+            let definition = element
+                .select(&definition_selector)
+                .next()
+                .and_then(|definition| description(definition.inner_html()))
+                .filter(|definition| !definition.is_empty());
+            let definition = match definition {
+                Some(definition) => definition,
+                None => continue,
+            };
+
+            senses.push(Sense {
+                definition,
+                part_of_speech: pos_selector
+                    .as_ref()
+                    .and_then(|pos| first_text_in(element, pos))
+                    .or_else(|| block_pos.clone()),
+                grammar: grammar_selector.as_ref().and_then(|grammar| first_text_in(element, grammar)),
+                examples: examples_selector
+                    .as_ref()
+                    .map(|examples| texts_in(element, examples))
+                    .unwrap_or_default(),
+                cefr: cefr(element, queries.cefr.as_deref()),
+                domains: domains_selector
+                    .as_ref()
+                    .map(|domains| texts_in(element, domains))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|domain| domain.trim_matches(|c: char| "() ".contains(c)).to_string())
+                    .filter(|domain| !domain.is_empty())
+                    .collect(),
+                region: regions.get(&element.id()).cloned(),
+                sources: vec![],
+            });
+        }
+    }
+    senses
+}
+
+fn cefr(element: ElementRef, query: Option<&str>) -> Option<String> {
+    let level = match query? {
+        attribute if attribute.starts_with('@') => element.value().attr(&attribute[1..]).map(|level| level.to_string()),
+        query => first_text_in(element, &selector(query)?),
+    };
+    level
+        .map(|level| level.trim().to_uppercase())
+        .filter(|level| !level.is_empty())
+}
+
+fn selector(query: &str) -> Option<Selector> {
+    Selector::parse(query).ok()
+}
+
+fn element_text(element: ElementRef) -> String {
+    element.text().collect::<Vec<_>>().join("").split_whitespace().join(" ")
+}
+
+fn first_text_in(element: ElementRef, selector: &Selector) -> Option<String> {
+    element
+        .select(selector)
+        .map(element_text)
+        .find(|text| !text.is_empty())
+}
+
+fn texts_in(element: ElementRef, selector: &Selector) -> Vec<String> {
+    element
+        .select(selector)
+        .map(element_text)
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+fn is_phrasal_verb(word: &str) -> bool {
+    word.split_whitespace().count() > 1
+}
+
+pub(crate) fn first_text(query: &str, html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    first_text_in(document.root_element(), &selector(query)?)
+}
+
+pub fn not_found(message: &str) -> ServiceError {
+    ServiceError {
+        message: message.to_string(),
+        error_type: ServiceErrorType::ResourceNotFound,
+    }
+}
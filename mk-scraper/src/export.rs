@@ -0,0 +1,505 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use chrono::Utc;
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::{file_name, from_file, list_all_files},
+};
+use log::{error, info};
+use rusqlite::{params, Connection};
+use serde_json::json;
+
+use crate::{
+    constants::{JSON_EXT, MP3_EXT},
+    glossary::Glossary,
+    model::DictionaryEntry,
+};
+
+pub const EXPORT_DIR: &str = "data/export";
+/// Anki separates the fields of a note with the unit separator.
+const FIELD_SEPARATOR: &str = "\u{1f}";
+const COLLECTION: &str = "collection.anki2";
+const MEDIA: &str = "media";
+/// The note type is the same for every deck, so re-importing updates notes.
+const MODEL_ID: i64 = 1_683_212_304_001;
+const FIELDS: [&str; 5] = ["Word", "Definitions", "Examples", "Translation", "Audio"];
+
+/// One flashcard: what a deck needs of an entry and its translation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Card {
+    pub word: String,
+    pub definitions: Vec<String>,
+    pub examples: Vec<String>,
+    pub translation: Option<String>,
+    /// The downloaded MP3.
+    pub audio: Option<String>,
+}
+
+impl Card {
+    /// The audio is the entry's `file`, or `<mp3_dir>/<word>.mp3` when it exists.
+    pub fn new(entry: &DictionaryEntry, glossary: &Glossary, mp3_dir: &str) -> Self {
+        let definitions = if entry.senses.is_empty() {
+            entry.definitions.clone()
+        } else {
+            entry.senses.iter().map(|sense| sense.definition.clone()).collect()
+        };
+        let word = strip_tags(&entry.word);
+        let mp3_file = file_name(mp3_dir, entry.word.trim().to_lowercase().as_str(), MP3_EXT);
+        Card {
+            word,
+            definitions,
+            examples: entry.examples(),
+            translation: glossary
+                .get(&entry.word)
+                .filter(|translation| !translation.is_empty())
+                .map(|translation| translation.value.clone()),
+            audio: entry
+                .file
+                .clone()
+                .into_iter()
+                .chain([mp3_file])
+                .find(|file| Path::new(file).exists()),
+        }
+    }
+
+    /// File name of the audio inside the deck, e.g. `wind up.mp3`.
+    fn audio_name(&self) -> Option<String> {
+        self.audio
+            .as_ref()
+            .and_then(|file| Path::new(file).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+    }
+}
+
+/// Cards of the entries saved in `json_dir`, of the given words only when
+/// there are any, one per word. Broken files are logged and skipped.
+pub fn load_cards(
+    json_dir: &str,
+    mp3_dir: &str,
+    words: &[String],
+    glossary: &Glossary,
+) -> ServiceExuctionResult<Vec<Card>> {
+    let mut files = list_all_files(json_dir).map_err(|err| ServiceError {
+        message: format!("{}: {}", err.message, json_dir),
+        error_type: ServiceErrorType::ResourceNotFound,
+    })?;
+    files.retain(|file| file.ends_with(JSON_EXT));
+    files.sort();
+
+    let mut cards: Vec<Card> = vec![];
+    for file in files {
+        match from_file::<&str, DictionaryEntry>(file.as_str()) {
+            Some(entry) if words.is_empty() || words.contains(&entry.word.trim().to_lowercase()) => {
+                let card = Card::new(&entry, glossary, mp3_dir);
+                if !cards.iter().any(|known| known.word.eq_ignore_ascii_case(&card.word)) {
+                    cards.push(card);
+                }
+            }
+            Some(_) => continue,
+            None => error!("entry.skipped: {}", file),
+        }
+    }
+    Ok(cards)
+}
+
+/// A plain deck with a header row; `delimiter` is `b','` for CSV and
+/// `b'\t'` for TSV. The lists are joined with `; ` and the audio is the
+/// MP3 path.
+pub fn export_delimited(cards: &[Card], file: &str, delimiter: u8) -> ServiceExuctionResult<()> {
+    create_parent(file)?;
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_path(file)
+        .map_err(csv_error)?;
+    writer.write_record(FIELDS.map(str::to_lowercase)).map_err(csv_error)?;
+    for card in cards {
+        writer
+            .write_record([
+                card.word.clone(),
+                card.definitions.join("; "),
+                card.examples.join("; "),
+                card.translation.clone().unwrap_or_default(),
+                card.audio.clone().unwrap_or_default(),
+            ])
+            .map_err(csv_error)?;
+    }
+    writer.flush()?;
+    info!("deck.exported: {} cards -> {}", cards.len(), file);
+    Ok(())
+}
+
+/// An Anki package: a zip of the SQLite collection, the `media` map and
+/// the MP3s named `0`, `1`, ...
+pub fn export_apkg(cards: &[Card], file: &str, deck_name: &str) -> ServiceExuctionResult<()> {
+    create_parent(file)?;
+    let collection = format!("{}.{}", file, COLLECTION);
+    let _ = fs::remove_file(&collection);
+    write_collection(cards, &collection, deck_name).map_err(sqlite_error)?;
+
+    let mut zip = zip::ZipWriter::new(File::create(file)?);
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file(COLLECTION, options).map_err(zip_error)?;
+    zip.write_all(&fs::read(&collection)?)?;
+    fs::remove_file(&collection)?;
+
+    let mut media = serde_json::Map::new();
+    for card in cards {
+        if let (Some(audio), Some(name)) = (&card.audio, card.audio_name()) {
+            let index = media.len().to_string();
+            zip.start_file(index.as_str(), options).map_err(zip_error)?;
+            zip.write_all(&fs::read(audio)?)?;
+            media.insert(index, json!(name));
+        }
+    }
+    zip.start_file(MEDIA, options).map_err(zip_error)?;
+    zip.write_all(serde_json::Value::Object(media).to_string().as_bytes())?;
+    zip.finish().map_err(zip_error)?;
+    info!("deck.exported: {} cards -> {}", cards.len(), file);
+    Ok(())
+}
+
+fn write_collection(cards: &[Card], file: &str, deck_name: &str) -> rusqlite::Result<()> {
+    let mut connection = Connection::open(file)?;
+    let now = Utc::now();
+    let seconds = now.timestamp();
+    let millis = now.timestamp_millis();
+    let deck_id = 1_000_000_000 + checksum(deck_name) % 1_000_000_000;
+
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SCHEMA)?;
+    transaction.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            seconds,
+            millis,
+            millis,
+            configuration(deck_id).to_string(),
+            models(deck_id, seconds).to_string(),
+            decks(deck_id, deck_name, seconds).to_string(),
+            deck_options().to_string()
+        ],
+    )?;
+    for (index, card) in cards.iter().enumerate() {
+        let note_id = millis + index as i64;
+        transaction.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, '', ?5, ?6, ?7, 0, '')",
+            params![
+                note_id,
+                guid(deck_name, &card.word),
+                MODEL_ID,
+                seconds,
+                fields(card).join(FIELD_SEPARATOR),
+                card.word,
+                checksum(&card.word)
+            ],
+        )?;
+        transaction.execute(
+            "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![note_id, note_id, deck_id, seconds, index as i64 + 1],
+        )?;
+    }
+    transaction.commit()
+}
+
+/// The note fields as HTML.
+fn fields(card: &Card) -> Vec<String> {
+    let list = |items: &[String]| match items.len() {
+        0 => String::new(),
+        1 => escape(&items[0]),
+        _ => format!(
+            "<ol>{}</ol>",
+            items.iter().map(|item| format!("<li>{}</li>", escape(item))).collect::<String>()
+        ),
+    };
+    vec![
+        escape(&card.word),
+        list(&card.definitions),
+        list(&card.examples),
+        card.translation.as_deref().map(escape).unwrap_or_default(),
+        card.audio_name().map(|name| format!("[sound:{}]", name)).unwrap_or_default(),
+    ]
+}
+
+/// Older entries keep markup in the headword, e.g. `beef <span>sth</span> up`.
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Anki's note checksum: the first 8 hex digits of the SHA-1 of the field.
+fn checksum(text: &str) -> i64 {
+    let digest = sha1_smol::Sha1::from(text).digest().to_string();
+    i64::from_str_radix(&digest[..8], 16).unwrap_or_default()
+}
+
+/// Stable per deck and word, so importing the deck again updates the notes.
+fn guid(deck_name: &str, word: &str) -> String {
+    sha1_smol::Sha1::from(format!("{}{}{}", deck_name, FIELD_SEPARATOR, word))
+        .digest()
+        .to_string()[..10]
+        .to_string()
+}
+
+fn configuration(deck_id: i64) -> serde_json::Value {
+    json!({
+        "nextPos": 1, "estTimes": true, "activeDecks": [deck_id], "sortType": "noteFld", "timeLim": 0,
+        "sortBackwards": false, "addToCur": true, "curDeck": deck_id, "newBury": true, "newSpread": 0,
+        "dueCounts": true, "curModel": MODEL_ID.to_string(), "collapseTime": 1200
+    })
+}
+
+fn models(deck_id: i64, modified: i64) -> serde_json::Value {
+    let fields: Vec<serde_json::Value> = FIELDS
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| {
+            json!({"name": name, "ord": ord, "font": "Arial", "size": 20, "media": [], "rtl": false, "sticky": false})
+        })
+        .collect();
+    json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID, "name": "matkat", "type": 0, "mod": modified, "usn": -1, "sortf": 0, "did": deck_id,
+            "tags": [], "vers": [], "req": [[0, "any", [0, 4]]], "flds": fields,
+            "tmpls": [{
+                "name": "Card 1", "ord": 0, "did": null, "bqfmt": "", "bafmt": "",
+                "qfmt": "<div class=word>{{Word}}</div>{{Audio}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Definitions}}<div class=examples>{{Examples}}</div><div class=translation>{{Translation}}</div>"
+            }],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: left; color: black; background-color: white; }\n.word { font-size: 28px; text-align: center; }\n.examples { font-style: italic; }\n.translation { margin-top: 12px; color: #1a5fb4; }",
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "latexPost": "\\end{document}"
+        }
+    })
+}
+
+fn decks(deck_id: i64, deck_name: &str, modified: i64) -> serde_json::Value {
+    let deck = |id: i64, name: &str| {
+        json!({
+            "id": id, "name": name, "desc": "", "conf": 1, "dyn": 0, "collapsed": false, "usn": -1, "mod": modified,
+            "extendNew": 10, "extendRev": 50, "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0],
+            "timeToday": [0, 0]
+        })
+    };
+    json!({"1": deck(1, "Default"), deck_id.to_string(): deck(deck_id, deck_name)})
+}
+
+fn deck_options() -> serde_json::Value {
+    json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60, "autoplay": true, "timer": 0,
+            "replayq": true, "dyn": false,
+            "new": {"delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "perDay": 20,
+                "separate": true, "bury": true},
+            "lapse": {"delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0},
+            "rev": {"perDay": 100, "ease4": 1.3, "fuzz": 0.05, "minSpace": 1, "ivlFct": 1, "maxIvl": 36500,
+                "bury": true}
+        }
+    })
+}
+
+/// Version 11 of the Anki collection schema, which every Anki release imports.
+const SCHEMA: &str = "
+CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null, conf text not null,
+    models text not null, decks text not null, dconf text not null, tags text not null);
+CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null, csum integer not null,
+    flags integer not null, data text not null);
+CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null, due integer not null,
+    ivl integer not null, factor integer not null, reps integer not null, lapses integer not null,
+    left integer not null, odue integer not null, odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+    type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn ON notes (usn);
+CREATE INDEX ix_cards_usn ON cards (usn);
+CREATE INDEX ix_revlog_usn ON revlog (usn);
+CREATE INDEX ix_cards_nid ON cards (nid);
+CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+CREATE INDEX ix_revlog_cid ON revlog (cid);
+CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+fn create_parent(file: &str) -> ServiceExuctionResult<()> {
+    if let Some(dir) = Path::new(file).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+fn csv_error(err: csv::Error) -> ServiceError {
+    ServiceError {
+        message: format!("invalid.csv: {}", err),
+        error_type: ServiceErrorType::IOError,
+    }
+}
+
+fn sqlite_error(err: rusqlite::Error) -> ServiceError {
+    ServiceError {
+        message: format!("invalid.collection: {}", err),
+        error_type: ServiceErrorType::Failure,
+    }
+}
+
+fn zip_error(err: zip::result::ZipError) -> ServiceError {
+    ServiceError {
+        message: format!("invalid.package: {}", err),
+        error_type: ServiceErrorType::IOError,
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use std::{fs, io::Read};
+
+    use common_libs::files::{save, temp_dir};
+    use rusqlite::Connection;
+
+    use crate::{
+        glossary::{Glossary, Translation},
+        model::{Dictionary, DictionaryEntry, Sense},
+    };
+
+    use super::{checksum, export_apkg, export_delimited, fields, load_cards, strip_tags, Card, FIELD_SEPARATOR};
+
+    const MP3: &[u8] = &[0xFF, 0xFB, 0x50, 0xC0, 0x00];
+
+    fn cards() -> Vec<Card> {
+        let dir = temp_dir("mk_scraper_export_cards");
+        let audio = format!("{}/wind up.mp3", dir);
+        fs::write(&audio, MP3).unwrap();
+        vec![
+            Card {
+                word: "wind up".to_string(),
+                definitions: vec!["to end".to_string(), "to <tease> someone".to_string()],
+                examples: vec!["We wound up the meeting.".to_string()],
+                translation: Some("приключвам".to_string()),
+                audio: Some(audio),
+            },
+            Card {
+                word: "defer".to_string(),
+                definitions: vec!["to delay something".to_string()],
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn load_cards_test() {
+        let json_dir = temp_dir("mk_scraper_export_entries");
+        let entry = DictionaryEntry {
+            file: Some("missing.mp3".to_string()),
+            definitions: vec!["a feeling of nervousness".to_string()],
+            senses: vec![Sense {
+                definition: "a feeling of nervousness".to_string(),
+                examples: vec!["You could feel the tension in the room.".to_string()],
+                ..Default::default()
+            }],
+            ..DictionaryEntry::new(Dictionary::Cambridge, "tension")
+        };
+        save(&format!("{}/tension.json", json_dir), &entry).unwrap();
+        save(&format!("{}/tension copy.json", json_dir), &entry).unwrap();
+        save(&format!("{}/defer.json", json_dir), &DictionaryEntry { word: "defer".to_string(), ..entry }).unwrap();
+        fs::write(format!("{}/defer.mp3", json_dir), MP3).unwrap();
+        fs::write(format!("{}/broken.json", json_dir), "{").unwrap();
+        fs::write(format!("{}/notes.txt", json_dir), "tension").unwrap();
+        let mut glossary = Glossary::default();
+        glossary.set(Translation {
+            key: "tension".to_string(),
+            value: "напрежение".to_string(),
+            description: String::new(),
+        });
+
+        let cards = load_cards(&json_dir, &json_dir, &[], &glossary).unwrap();
+        assert_eq!(vec!["defer", "tension"], cards.iter().map(|card| card.word.as_str()).collect::<Vec<_>>());
+        assert_eq!(None, cards[0].translation);
+        assert_eq!(Some("напрежение".to_string()), cards[1].translation);
+        assert_eq!(vec!["You could feel the tension in the room."], cards[1].examples);
+        assert_eq!(Some(format!("{}/defer.mp3", json_dir)), cards[0].audio);
+        assert_eq!(None, cards[1].audio);
+        assert_eq!(1, load_cards(&json_dir, &json_dir, &["tension".to_string()], &glossary).unwrap().len());
+        assert!(load_cards("missing/dir", &json_dir, &[], &glossary).is_err());
+    }
+
+    #[test]
+    fn export_delimited_test() {
+        let dir = temp_dir("mk_scraper_export_delimited");
+        let file = format!("{}/deck.tsv", dir);
+        export_delimited(&cards(), &file, b'\t').unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!("word\tdefinitions\texamples\ttranslation\taudio", lines[0]);
+        assert!(lines[1].starts_with("wind up\tto end; to <tease> someone\tWe wound up the meeting.\tприключвам\t"));
+        assert!(lines[1].ends_with("wind up.mp3"));
+        assert_eq!("defer\tto delay something\t\t\t", lines[2]);
+
+        let file = format!("{}/deck.csv", dir);
+        export_delimited(&cards(), &file, b',').unwrap();
+        assert!(fs::read_to_string(&file).unwrap().contains("defer,to delay something,,,"));
+    }
+
+    #[test]
+    fn fields_test() {
+        let fields = fields(&cards()[0]);
+        assert_eq!("<ol><li>to end</li><li>to &lt;tease&gt; someone</li></ol>", fields[1]);
+        assert_eq!("We wound up the meeting.", fields[2]);
+        assert_eq!("[sound:wind up.mp3]", fields[4]);
+        assert_eq!("beef sth up", strip_tags("beef <span class=\"obj dobj\">sth</span> up"));
+        assert_eq!(i64::from_str_radix("d2cc9941", 16).unwrap(), checksum("defer"));
+    }
+
+    #[test]
+    fn export_apkg_test() {
+        let dir = temp_dir("mk_scraper_export_apkg");
+        let file = format!("{}/deck.apkg", dir);
+        export_apkg(&cards(), &file, "matkat::cambridge").unwrap();
+        assert!(!std::path::Path::new(&format!("{}.collection.anki2", file)).exists());
+
+        let mut package = zip::ZipArchive::new(fs::File::open(&file).unwrap()).unwrap();
+        let mut media = String::new();
+        package.by_name("media").unwrap().read_to_string(&mut media).unwrap();
+        assert_eq!(r#"{"0":"wind up.mp3"}"#, media);
+        let mut audio = vec![];
+        package.by_name("0").unwrap().read_to_end(&mut audio).unwrap();
+        assert_eq!(MP3, audio);
+
+        let collection = format!("{}/collection.anki2", dir);
+        let mut content = vec![];
+        package.by_name("collection.anki2").unwrap().read_to_end(&mut content).unwrap();
+        fs::write(&collection, content).unwrap();
+        let connection = Connection::open(&collection).unwrap();
+        let decks: String = connection.query_row("SELECT decks FROM col", [], |row| row.get(0)).unwrap();
+        assert!(decks.contains("matkat::cambridge"));
+        let mut statement = connection.prepare("SELECT flds, sfld, csum FROM notes ORDER BY id").unwrap();
+        let notes: Vec<(String, String, i64)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(2, notes.len());
+        assert_eq!(5, notes[0].0.split(FIELD_SEPARATOR).count());
+        assert!(notes[0].0.ends_with("приключвам\u{1f}[sound:wind up.mp3]"));
+        assert_eq!(("defer".to_string(), checksum("defer")), (notes[1].1.clone(), notes[1].2));
+        let cards: i64 = connection.query_row("SELECT count(*) FROM cards", [], |row| row.get(0)).unwrap();
+        assert_eq!(2, cards);
+    }
+}
@@ -0,0 +1,256 @@
+use std::{fs, path::Path, str::FromStr};
+
+use async_trait::async_trait;
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::file_name,
+};
+use log::{error, info};
+
+use crate::{
+    cache::key,
+    constants::HTML_EXT,
+    http_client::{self, Fetcher, HttpClient},
+};
+
+/// `record` or `replay`; unset means the scrapers go to the live websites,
+/// except in this crate's own tests, which replay by default. The fixtures are
+/// re-recorded with `MK_SCRAPER_FIXTURES=record cargo test -p mk_scraper -- --include-ignored`.
+pub const FIXTURES_ENV: &str = "MK_SCRAPER_FIXTURES";
+pub const FIXTURES_DIR_ENV: &str = "MK_SCRAPER_FIXTURES_DIR";
+pub const FIXTURES_DIR: &str = "fixtures";
+pub const NOT_FOUND_EXT: &str = ".notfound";
+/// Recorded binary responses, e.g. pronunciations.
+pub const BINARY_EXT: &str = ".bin";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixtureMode {
+    /// Fetch live and write every response into the fixtures directory.
+    Record,
+    /// Serve responses from the fixtures directory only, never touching the network.
+    Replay,
+}
+
+impl FromStr for FixtureMode {
+    type Err = ServiceError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source.to_lowercase().trim() {
+            "record" => Ok(FixtureMode::Record),
+            "replay" => Ok(FixtureMode::Replay),
+            other => Err(ServiceError {
+                message: format!("invalid.fixtures.mode: {} (expected record or replay)", other),
+                error_type: ServiceErrorType::Failure,
+            }),
+        }
+    }
+}
+
+/// Recorded pages live in `<dir>/<key>.html` and binary responses in
+/// `<dir>/<key>.bin`, where `key` is the cache key of the URL. Resources the
+/// site reported as missing are recorded as `<key>.notfound`.
+#[derive(Debug, Clone)]
+pub struct FixtureFetcher {
+    dir: String,
+    mode: FixtureMode,
+    live: HttpClient,
+}
+
+impl FixtureFetcher {
+    pub fn replay<T: AsRef<str>>(dir: T) -> Self {
+        FixtureFetcher {
+            dir: dir.as_ref().to_string(),
+            mode: FixtureMode::Replay,
+            live: http_client::shared().clone(),
+        }
+    }
+
+    /// Pages are fetched past the HTML cache, so a stale cached page is never recorded.
+    pub fn record<T: AsRef<str>>(dir: T, live: HttpClient) -> Self {
+        FixtureFetcher {
+            dir: dir.as_ref().to_string(),
+            mode: FixtureMode::Record,
+            live: live.without_cache(),
+        }
+    }
+
+    /// The mode set in `MK_SCRAPER_FIXTURES`; an unknown one is an error.
+    pub fn mode_from_env() -> ServiceExuctionResult<Option<FixtureMode>> {
+        match std::env::var(FIXTURES_ENV) {
+            Ok(mode) => Ok(Some(mode.parse::<FixtureMode>()?)),
+            Err(_) if cfg!(test) => Ok(Some(FixtureMode::Replay)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub fn from_env() -> ServiceExuctionResult<Option<Self>> {
+        let mode = match FixtureFetcher::mode_from_env()? {
+            Some(mode) => mode,
+            None => return Ok(None),
+        };
+        let dir = std::env::var(FIXTURES_DIR_ENV).unwrap_or_else(|_| FIXTURES_DIR.to_string());
+        info!("{:?} fixtures from {}", mode, dir);
+        Ok(Some(match mode {
+            FixtureMode::Record => FixtureFetcher::record(dir, http_client::shared().clone()),
+            FixtureMode::Replay => FixtureFetcher::replay(dir),
+        }))
+    }
+
+    fn replay_page(&self, url: &str) -> ServiceExuctionResult<String> {
+        let key = key(url);
+        let html_file = file_name(self.dir.as_str(), key.as_str(), HTML_EXT);
+        if Path::new(&html_file).exists() {
+            return Ok(fs::read_to_string(html_file)?);
+        }
+        Err(self.not_recorded(url, &key))
+    }
+
+    fn replay_binary(&self, url: &str) -> ServiceExuctionResult<Vec<u8>> {
+        let key = key(url);
+        let binary_file = file_name(self.dir.as_str(), key.as_str(), BINARY_EXT);
+        if Path::new(&binary_file).exists() {
+            return Ok(fs::read(binary_file)?);
+        }
+        Err(self.not_recorded(url, &key))
+    }
+
+    fn not_recorded(&self, url: &str, key: &str) -> ServiceError {
+
+        if Path::new(&file_name(self.dir.as_str(), key, NOT_FOUND_EXT)).exists() {
+            return ServiceError {
+                message: "not.found".to_string(),
+                error_type: ServiceErrorType::ResourceNotFound,
+            };
+        }
+
+        ServiceError {
+            message: format!("fixture.not.recorded: {}", url),
+            error_type: ServiceErrorType::Failure,
+        }
+    }
+
+    fn record_page(&self, url: &str, result: &ServiceExuctionResult<String>) {
+        self.write_record(url, result.as_ref().map(|html| html.as_bytes()), HTML_EXT)
+    }
+
+    fn record_binary(&self, url: &str, result: &ServiceExuctionResult<Vec<u8>>) {
+        self.write_record(url, result.as_ref().map(|bytes| bytes.as_slice()), BINARY_EXT)
+    }
+
+    fn write_record(&self, url: &str, result: Result<&[u8], &ServiceError>, ext: &str) {
+        let key = key(url);
+        let written = match result {
+            Ok(content) => fs::create_dir_all(&self.dir)
+                .and_then(|_| fs::write(file_name(self.dir.as_str(), key.as_str(), ext), content)),
+            Err(err) if err.error_type == ServiceErrorType::ResourceNotFound => fs::create_dir_all(&self.dir)
+                .and_then(|_| fs::write(file_name(self.dir.as_str(), key.as_str(), NOT_FOUND_EXT), url)),
+            Err(_) => return,
+        };
+        match written {
+            Ok(_) => info!("recorded: {} -> {}", url, key),
+            Err(err) => error!("failed.to.record: {} -> {}", url, err),
+        }
+    }
+}
+
+#[async_trait]
+impl Fetcher for FixtureFetcher {
+    async fn get_text(&self, url: &str) -> ServiceExuctionResult<String> {
+        match self.mode {
+            FixtureMode::Replay => self.replay_page(url),
+            FixtureMode::Record => {
+                let result = self.live.get_text(url).await;
+                self.record_page(url, &result);
+                result
+            }
+        }
+    }
+
+    async fn browser_get_text(&self, url: &str) -> ServiceExuctionResult<String> {
+        match self.mode {
+            FixtureMode::Replay => self.replay_page(url),
+            FixtureMode::Record => {
+                let result = self.live.browser_get_text(url).await;
+                self.record_page(url, &result);
+                result
+            }
+        }
+    }
+
+    async fn get_bytes(&self, url: &str) -> ServiceExuctionResult<Vec<u8>> {
+        match self.mode {
+            FixtureMode::Replay => self.replay_binary(url),
+            FixtureMode::Record => {
+                let result = self.live.get_bytes(url).await;
+                self.record_binary(url, &result);
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod fixtures_tests {
+    use common_libs::{
+        error::{ServiceError, ServiceErrorType},
+        files::temp_dir,
+    };
+
+    use crate::http_client::{self, Fetcher};
+
+    use super::{FixtureFetcher, FixtureMode, FIXTURES_DIR};
+
+    #[test]
+    fn mode_test() {
+        assert_eq!(FixtureMode::Record, "Record".parse::<FixtureMode>().unwrap());
+        assert_eq!(FixtureMode::Replay, " replay".parse::<FixtureMode>().unwrap());
+        assert!("off".parse::<FixtureMode>().is_err());
+        assert!("".parse::<FixtureMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn replay_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let html = fixtures
+            .get_text("https://dictionary.cambridge.org/dictionary/english/correct")
+            .await;
+        assert!(html.is_ok());
+        assert!(html.unwrap().contains("def ddef_d db"));
+
+        let not_found = fixtures
+            .get_text("https://www.oxfordlearnersdictionaries.com/definition/english/correct_3")
+            .await;
+        assert_eq!(ServiceErrorType::ResourceNotFound, not_found.unwrap_err().error_type);
+
+        let missing = fixtures
+            .get_text("https://dictionary.cambridge.org/dictionary/english/not-recorded")
+            .await;
+        assert_eq!(ServiceErrorType::Failure, missing.unwrap_err().error_type);
+    }
+
+    #[tokio::test]
+    async fn record_test() {
+        let dir = temp_dir("mk_scraper_fixtures_tests");
+        let recorder = FixtureFetcher::record(&dir, http_client::shared().clone());
+        assert!(http_client::shared().cache().is_some());
+        assert!(recorder.live.cache().is_none());
+
+        let url = "https://www.collinsdictionary.com/dictionary/english/recorded";
+        recorder.record_page(url, &Ok("<html>recorded</html>".to_string()));
+        let missing = "https://www.collinsdictionary.com/dictionary/english/missing";
+        recorder.record_page(
+            missing,
+            &Err(ServiceError {
+                message: "not.found".to_string(),
+                error_type: ServiceErrorType::ResourceNotFound,
+            }),
+        );
+
+        let replay = FixtureFetcher::replay(&dir);
+        assert_eq!("<html>recorded</html>", replay.get_text(url).await.unwrap());
+        assert_eq!(
+            ServiceErrorType::ResourceNotFound,
+            replay.browser_get_text(missing).await.unwrap_err().error_type
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::{fs, path::Path};
+
+use common_libs::error::{ServiceError, ServiceErrorType, ServiceExuctionResult};
+use serde::{Deserialize, Serialize};
+
+use crate::model::DictionaryEntry;
+
+/// English key, Bulgarian value and an optional description.
+pub const TRANSLATIONS_FILE: &str = "data/translations.csv";
+/// Alternative translations in one value, e.g. `рекламиран; натравам`.
+const VALUE_SEPARATOR: char = ';';
+/// Bulgarian verbs are listed with their particle, `да отменя`.
+const PARTICLES: [&str; 2] = ["да ", "за "];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Translation {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Translation {
+    pub fn is_empty(&self) -> bool {
+        self.value.trim().is_empty()
+    }
+
+    /// The alternative translations of the value.
+    pub fn values(&self) -> Vec<String> {
+        self.value
+            .split(VALUE_SEPARATOR)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The values, and the verbs also without their particle, so both
+    /// `да отменя` and `отменя` are accepted.
+    pub fn answers(&self) -> Vec<String> {
+        let mut answers = self.values();
+        for value in self.values() {
+            if let Some(verb) = PARTICLES.iter().find_map(|particle| value.strip_prefix(particle)) {
+                answers.push(verb.trim().to_string());
+            }
+        }
+        answers
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Glossary {
+    pub translations: Vec<Translation>,
+}
+
+impl Glossary {
+    /// The glossary in the CSV file; an empty one when there is no file yet.
+    pub fn load(file: &str) -> ServiceExuctionResult<Self> {
+        if !Path::new(file).exists() {
+            return Ok(Glossary::default());
+        }
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(file).map_err(csv_error)?;
+        let mut translations = vec![];
+        for record in reader.deserialize() {
+            let translation: Translation = record.map_err(csv_error)?;
+            if !translation.key.trim().is_empty() {
+                translations.push(translation);
+            }
+        }
+        Ok(Glossary { translations })
+    }
+
+    /// Writes a temporary file and renames it, like the statistic.
+    pub fn save(&self, file: &str) -> ServiceExuctionResult<()> {
+        if let Some(dir) = Path::new(file).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp_file = format!("{}.tmp", file);
+        let mut writer = csv::Writer::from_path(&tmp_file).map_err(csv_error)?;
+        for translation in &self.translations {
+            writer.serialize(translation).map_err(csv_error)?;
+        }
+        writer.flush()?;
+        fs::rename(&tmp_file, file)?;
+        Ok(())
+    }
+
+    pub fn get(&self, word: &str) -> Option<&Translation> {
+        let word = word.trim().to_lowercase();
+        self.translations
+            .iter()
+            .find(|translation| translation.key.trim().to_lowercase() == word)
+    }
+
+    /// Adds the translation or replaces the one with the same key.
+    pub fn set(&mut self, translation: Translation) {
+        let key = translation.key.trim().to_lowercase();
+        match self
+            .translations
+            .iter_mut()
+            .find(|existing| existing.key.trim().to_lowercase() == key)
+        {
+            Some(existing) => *existing = translation,
+            None => self.translations.push(translation),
+        }
+    }
+
+    /// Keys without a translation yet.
+    pub fn untranslated(&self) -> Vec<&str> {
+        self.translations
+            .iter()
+            .filter(|translation| translation.is_empty())
+            .map(|translation| translation.key.as_str())
+            .collect()
+    }
+
+    /// Every entry with its translation, if the glossary has a non-empty one.
+    pub fn join(&self, entries: Vec<DictionaryEntry>) -> Vec<(DictionaryEntry, Option<Translation>)> {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let translation = self.get(&entry.word).filter(|translation| !translation.is_empty()).cloned();
+                (entry, translation)
+            })
+            .collect()
+    }
+
+    /// Words of the entries that have no translation, either missing from
+    /// the glossary or with an empty value.
+    pub fn missing(&self, entries: &[DictionaryEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .filter(|entry| self.get(&entry.word).is_none_or(Translation::is_empty))
+            .map(|entry| entry.word.clone())
+            .collect()
+    }
+}
+
+fn csv_error(err: csv::Error) -> ServiceError {
+    ServiceError {
+        message: format!("invalid.csv: {}", err),
+        error_type: ServiceErrorType::SerializationError,
+    }
+}
+
+#[cfg(test)]
+mod glossary_tests {
+    use std::fs;
+
+    use common_libs::files::temp_dir;
+
+    use crate::model::{Dictionary, DictionaryEntry};
+
+    use super::{Glossary, Translation, TRANSLATIONS_FILE};
+
+    fn entry(word: &str) -> DictionaryEntry {
+        DictionaryEntry::new(Dictionary::Cambridge, word)
+    }
+
+    #[test]
+    fn load_test() {
+        let glossary = Glossary::load(&format!("../{}", TRANSLATIONS_FILE)).unwrap();
+        let touted = glossary.get("Touted").unwrap();
+        assert_eq!(vec!["рекламиран", "натравам"], touted.values());
+        assert!(touted.description.starts_with("attempt to sell (something), typically"));
+        assert_eq!("да отменя", glossary.get("call off").unwrap().value);
+        assert!(glossary.untranslated().contains(&"swiftly"));
+        assert!(glossary.untranslated().contains(&"wane"));
+        assert_eq!(Glossary::default(), Glossary::load("missing.csv").unwrap());
+    }
+
+    #[test]
+    fn answers_test() {
+        let translation = Translation {
+            key: "call off".to_string(),
+            value: "да отменя; анулирам".to_string(),
+            description: String::new(),
+        };
+        assert_eq!(vec!["да отменя", "анулирам", "отменя"], translation.answers());
+        assert!(Translation::default().is_empty());
+    }
+
+    #[test]
+    fn save_test() {
+        let file = format!("{}/translations.csv", temp_dir("mk_scraper_glossary_tests"));
+
+        let mut glossary = Glossary::load(&format!("../{}", TRANSLATIONS_FILE)).unwrap();
+        let size = glossary.translations.len();
+        glossary.set(Translation {
+            key: "Swiftly".to_string(),
+            value: "бързо".to_string(),
+            description: "quickly, \"promptly\"".to_string(),
+        });
+        glossary.set(Translation {
+            key: "defer".to_string(),
+            value: "отлагам".to_string(),
+            description: String::new(),
+        });
+        glossary.save(&file).unwrap();
+        assert!(fs::read_to_string(&file).unwrap().starts_with("key,value,description\n"));
+
+        let saved = Glossary::load(&file).unwrap();
+        assert_eq!(glossary, saved);
+        assert_eq!(size + 1, saved.translations.len());
+        assert_eq!("бързо", saved.get("swiftly").unwrap().value);
+        assert!(!saved.untranslated().contains(&"Swiftly"));
+    }
+
+    #[test]
+    fn join_test() {
+        let glossary = Glossary::load(&format!("../{}", TRANSLATIONS_FILE)).unwrap();
+        let joined = glossary.join(vec![entry("tension"), entry("wane"), entry("defer")]);
+        assert_eq!("напрежение", joined[0].1.as_ref().unwrap().value);
+        assert!(joined[1].1.is_none());
+        assert!(joined[2].1.is_none());
+        assert_eq!(
+            vec!["wane", "defer"],
+            glossary.missing(&[entry("tension"), entry("wane"), entry("defer")])
+        );
+    }
+}
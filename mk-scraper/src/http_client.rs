@@ -0,0 +1,557 @@
+use std::{sync::OnceLock, time::Duration};
+
+use async_trait::async_trait;
+use common_libs::error::{ServiceError, ServiceErrorType, ServiceExuctionResult};
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use rand::Rng;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, CACHE_CONTROL, ETAG,
+        LAST_MODIFIED, UPGRADE_INSECURE_REQUESTS,
+    },
+    redirect, Client, ClientBuilder, Proxy, StatusCode,
+};
+
+use crate::{
+    cache::{CacheConfig, HtmlCache},
+    constants::USER_AGENT,
+    fixtures::FixtureFetcher,
+};
+
+pub const CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const MAX_REDIRECTS: usize = 10;
+pub const POOL_MAX_IDLE_PER_HOST: usize = 8;
+pub const POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+static SHARED: OnceLock<HttpClient> = OnceLock::new();
+
+lazy_static! {
+    static ref DEFAULT_FETCHER: Box<dyn Fetcher> = match FixtureFetcher::from_env() {
+        Ok(Some(fixtures)) => Box::new(fixtures),
+        Ok(None) => Box::new(shared().clone()),
+        Err(err) => {
+            error!("{}. Using the live websites", err.message);
+            Box::new(shared().clone())
+        }
+    };
+}
+
+/// Where the scrapers get their HTML from. Implemented by [`HttpClient`] for
+/// live pages and by [`FixtureFetcher`] for recorded ones.
+#[async_trait]
+pub trait Fetcher: Send + Sync {
+    async fn get_text(&self, url: &str) -> ServiceExuctionResult<String>;
+
+    /// Same page through the browser-like profile, for sites that reject the plain client.
+    async fn browser_get_text(&self, url: &str) -> ServiceExuctionResult<String>;
+
+    /// Raw body, e.g. a pronunciation; never cached.
+    async fn get_bytes(&self, url: &str) -> ServiceExuctionResult<Vec<u8>>;
+}
+
+/// A response with its body already read. The body is read inside the retry
+/// loop, so a connection dropped half way through it is retried as well.
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Fetched {
+    pub fn text(self) -> String {
+        String::from_utf8(self.body)
+            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+    }
+
+    fn header(&self, name: HeaderName) -> Option<String> {
+        self.headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff capped by `max_delay`, jittered within the upper half.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self.backoff(attempt).as_millis() as u64;
+        if ceiling == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(ceiling / 2..=ceiling))
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub proxy: Option<String>,
+    pub user_agent: String,
+    pub retry: RetryPolicy,
+    /// When set, pages are served from disk before going to the network.
+    /// On by default; `None` always goes to the network.
+    pub cache: Option<CacheConfig>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        HttpClientConfig {
+            pool_max_idle_per_host: POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout: Duration::from_secs(POOL_IDLE_TIMEOUT_SECS),
+            connect_timeout: Duration::from_secs(CONNECT_TIMEOUT_SECS),
+            request_timeout: Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            proxy: None,
+            user_agent: user_agent().to_string(),
+            retry: RetryPolicy::default(),
+            cache: Some(CacheConfig::default()),
+        }
+    }
+}
+
+/// Pooled HTTP clients shared by all scrapers. Cloning is cheap: the
+/// underlying connection pools are reference counted.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: Client,
+    /// Second profile used when a dictionary rejects the plain client:
+    /// browser-like headers, a cookie jar and compressed responses.
+    browser: Client,
+    retry: RetryPolicy,
+    cache: Option<HtmlCache>,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpClientConfig) -> ServiceExuctionResult<Self> {
+        let client = builder(config).build()?;
+        let browser = builder(config)
+            .default_headers(browser_headers())
+            .cookie_store(true)
+            .gzip(true)
+            .brotli(true)
+            .redirect(redirect::Policy::limited(MAX_REDIRECTS))
+            .build()?;
+        Ok(HttpClient {
+            client,
+            browser,
+            retry: config.retry.clone(),
+            cache: config.cache.clone().map(HtmlCache::new),
+        })
+    }
+
+    pub fn cache(&self) -> Option<&HtmlCache> {
+        self.cache.as_ref()
+    }
+
+    /// The same pools and retries, always going to the network.
+    pub fn without_cache(&self) -> Self {
+        HttpClient {
+            cache: None,
+            ..self.clone()
+        }
+    }
+
+    /// GET with retries for transient failures (timeouts, 5xx, dropped connections).
+    /// Not-found and other client errors are returned immediately.
+    pub async fn get(&self, url: &str) -> ServiceExuctionResult<Fetched> {
+        get_with_policy(&self.client, url, HeaderMap::new(), &self.retry).await
+    }
+
+    /// Same as [`HttpClient::get`] but through the browser-like client profile.
+    pub async fn browser_get(&self, url: &str) -> ServiceExuctionResult<Fetched> {
+        get_with_policy(&self.browser, url, HeaderMap::new(), &self.retry).await
+    }
+
+    /// Page body as text, going through the HTML cache when one is configured.
+    /// Stale pages are revalidated with their ETag/Last-Modified and served as
+    /// they are if the network is unreachable.
+    pub async fn get_text(&self, url: &str) -> ServiceExuctionResult<String> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return Ok(self.get(url).await?.text()),
+        };
+
+        let cached = cache.lookup(url);
+        let mut headers = HeaderMap::new();
+        if let Some((page, html)) = &cached {
+            if cache.is_fresh(page) {
+                debug!("cache.hit: {}", url);
+                return Ok(html.clone());
+            }
+            if !cache.config().force_refresh {
+                headers = page.conditional_headers();
+            }
+        }
+
+        match get_with_policy(&self.client, url, headers, &self.retry).await {
+            Ok(response) if response.status == StatusCode::NOT_MODIFIED && cached.is_some() => {
+                let (page, html) = cached.unwrap();
+                info!("not.modified: {}", url);
+                cache.touch(&page);
+                Ok(html)
+            }
+            Ok(response) => {
+                let etag = response.header(ETAG);
+                let last_modified = response.header(LAST_MODIFIED);
+                let html = response.text();
+                if let Err(err) = cache.store(url, &html, etag, last_modified) {
+                    error!("failed.to.cache: {} -> {}", url, err.message);
+                }
+                Ok(html)
+            }
+            Err(err) => match cached {
+                Some((_, html)) if err.error_type != ServiceErrorType::ResourceNotFound => {
+                    warn!("serving.stale.page: {} ({})", url, err.message);
+                    Ok(html)
+                }
+                _ => Err(err),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Fetcher for HttpClient {
+    async fn get_text(&self, url: &str) -> ServiceExuctionResult<String> {
+        HttpClient::get_text(self, url).await
+    }
+
+    async fn browser_get_text(&self, url: &str) -> ServiceExuctionResult<String> {
+        let html = String::from_utf8(self.browser_get(url).await?.body)?;
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.store(url, &html, None, None) {
+                error!("failed.to.cache: {} -> {}", url, err.message);
+            }
+        }
+        Ok(html)
+    }
+
+    async fn get_bytes(&self, url: &str) -> ServiceExuctionResult<Vec<u8>> {
+        let response = match self.get(url).await {
+            Ok(response) => response,
+            Err(err) if err.error_type == ServiceErrorType::ResourceNotFound => return Err(err),
+            Err(err) => {
+                error!("download.failed: {}. Trying as a browser...", err.message);
+                self.browser_get(url).await?
+            }
+        };
+        Ok(response.body)
+    }
+}
+
+/// Builds the shared client from `config`. Must be called before the first
+/// request; afterwards the client is already built and this fails. Fails as
+/// well when `MK_SCRAPER_FIXTURES` holds an unknown mode.
+pub fn init(config: HttpClientConfig) -> ServiceExuctionResult<()> {
+    FixtureFetcher::mode_from_env()?;
+    let client = HttpClient::new(&config)?;
+    SHARED.set(client).map_err(|_| ServiceError {
+        message: "http.client.already.initialized".to_string(),
+        error_type: ServiceErrorType::Failure,
+    })
+}
+
+/// The pooled client, for binary downloads and callers that need one. Built
+/// from the defaults unless [`init`] was called first.
+pub fn shared() -> &'static HttpClient {
+    SHARED.get_or_init(|| {
+        HttpClient::new(&HttpClientConfig::default()).expect("failed.to.build.http.client")
+    })
+}
+
+/// The fetcher used by the scrapers unless another one is injected: the
+/// shared client, or recorded fixtures when `MK_SCRAPER_FIXTURES` is set
+/// (replayed by default in this crate's tests).
+pub fn default_fetcher() -> &'static dyn Fetcher {
+    DEFAULT_FETCHER.as_ref()
+}
+
+pub fn user_agent() -> &'static str {
+    USER_AGENT.trim_matches('\'')
+}
+
+fn builder(config: &HttpClientConfig) -> ClientBuilder {
+    let mut builder = Client::builder()
+        .user_agent(config.user_agent.clone())
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .connect_timeout(config.connect_timeout)
+        .timeout(config.request_timeout);
+    if let Some(proxy) = &config.proxy {
+        match Proxy::all(proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(err) => error!("invalid.proxy: {} -> {}", proxy, err),
+        }
+    }
+    builder
+}
+
+fn browser_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+    );
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-GB,en;q=0.9"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(UPGRADE_INSECURE_REQUESTS, HeaderValue::from_static("1"));
+    headers
+}
+
+fn status_error(response: &reqwest::Response) -> Option<ServiceError> {
+    let status = response.status();
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        return None;
+    }
+    Some(ServiceError {
+        message: format!("nok.status.code: {}", status.as_str()),
+        error_type: ServiceErrorType::from_status(status.as_u16()),
+    })
+}
+
+pub async fn get_with_policy(
+    client: &Client,
+    url: &str,
+    headers: HeaderMap,
+    policy: &RetryPolicy,
+) -> ServiceExuctionResult<Fetched> {
+    let mut attempt = 0;
+    loop {
+        let result = match send(client, url, headers.clone()).await {
+            Ok(fetched) => return Ok(fetched),
+            Err(err) => err,
+        };
+
+        if !result.error_type.is_transient() || attempt >= policy.max_retries {
+            debug!("giving.up: {} after {} attempt(s): {}", url, attempt + 1, result.message);
+            return Err(result);
+        }
+
+        let delay = policy.delay(attempt);
+        warn!(
+            "retrying: {} in {:?} ({}/{}): {}",
+            url,
+            delay,
+            attempt + 1,
+            policy.max_retries,
+            result.message
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+async fn send(client: &Client, url: &str, headers: HeaderMap) -> ServiceExuctionResult<Fetched> {
+    let response = client.get(url).headers(headers).send().await?;
+    if let Some(err) = status_error(&response) {
+        return Err(err);
+    }
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?.to_vec();
+    Ok(Fetched {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod http_client_tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    use common_libs::{error::ServiceErrorType, files::temp_dir};
+
+    use crate::{cache::CacheConfig, fetch_url_with};
+
+    use super::{init, shared, user_agent, HttpClient, HttpClientConfig, RetryPolicy};
+
+    /// Answers one connection after another with the given raw responses.
+    fn serve(responses: Vec<&'static [u8]>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response);
+            }
+        });
+        format!("http://{}/page", address)
+    }
+
+    fn quick_retries() -> HttpClientConfig {
+        HttpClientConfig {
+            retry: RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(10),
+            },
+            cache: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff_test() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+        assert_eq!(Duration::from_millis(100), policy.backoff(0));
+        assert_eq!(Duration::from_millis(400), policy.backoff(2));
+        assert_eq!(Duration::from_millis(1000), policy.backoff(4));
+        assert_eq!(Duration::from_millis(1000), policy.backoff(40));
+
+        for attempt in 0..6 {
+            let delay = policy.delay(attempt);
+            assert!(delay <= policy.backoff(attempt));
+            assert!(delay >= policy.backoff(attempt) / 2);
+        }
+    }
+
+    #[test]
+    fn classify_status_test() {
+        assert_eq!(ServiceErrorType::ResourceNotFound, ServiceErrorType::from_status(404));
+        assert_eq!(ServiceErrorType::ResourceNotFound, ServiceErrorType::from_status(410));
+        assert_eq!(ServiceErrorType::Unavailable, ServiceErrorType::from_status(503));
+        assert_eq!(ServiceErrorType::Unavailable, ServiceErrorType::from_status(429));
+        assert_eq!(ServiceErrorType::Timeout, ServiceErrorType::from_status(408));
+        assert_eq!(ServiceErrorType::Failure, ServiceErrorType::from_status(403));
+
+        assert!(ServiceErrorType::from_status(502).is_transient());
+        assert!(!ServiceErrorType::from_status(404).is_transient());
+        assert!(!ServiceErrorType::from_status(403).is_transient());
+    }
+
+    #[tokio::test]
+    async fn retry_truncated_body_test() {
+        let url = serve(vec![
+            b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\ntrunc",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nwhole",
+        ]);
+        let client = HttpClient::new(&quick_retries()).unwrap();
+        assert_eq!("whole", client.get_text(&url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_retried_test() {
+        let url = serve(vec![
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nwhole",
+        ]);
+        let client = HttpClient::new(&quick_retries()).unwrap();
+        let err = client.get_text(&url).await.unwrap_err();
+        assert_eq!(ServiceErrorType::ResourceNotFound, err.error_type);
+    }
+
+    #[tokio::test]
+    async fn cached_page_test() {
+        let url = serve(vec![
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nfirst",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nfresh",
+        ]);
+        let cached = CacheConfig {
+            dir: temp_dir("mk_scraper_http_client_tests"),
+            ..Default::default()
+        };
+        let client = HttpClient::new(&HttpClientConfig {
+            cache: Some(cached.clone()),
+            ..quick_retries()
+        })
+        .unwrap();
+        assert_eq!("first", client.get_text(&url).await.unwrap());
+        assert_eq!("first", client.get_text(&url).await.unwrap());
+
+        let refreshed = HttpClient::new(&HttpClientConfig {
+            cache: Some(CacheConfig {
+                force_refresh: true,
+                ..cached
+            }),
+            ..quick_retries()
+        })
+        .unwrap();
+        assert_eq!("fresh", refreshed.get_text(&url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn browser_fallback_bytes_test() {
+        let url = serve(vec![
+            b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\n\xff\xfb\x90\x00",
+        ]);
+        let client = HttpClient::new(&quick_retries()).unwrap();
+        let file = std::env::temp_dir().join("mk_scraper_browser_fallback.mp3");
+        let file = file.to_string_lossy().to_string();
+        assert!(fetch_url_with(&client, url, file.clone()).await.is_ok());
+        assert_eq!(vec![0xff, 0xfb, 0x90, 0x00], std::fs::read(&file).unwrap());
+    }
+
+    #[test]
+    fn user_agent_test() {
+        assert!(user_agent().starts_with("Mozilla/5.0"));
+        assert!(!user_agent().contains('\''));
+    }
+
+    #[test]
+    fn client_config_test() {
+        let config = HttpClientConfig::default();
+        assert_eq!(user_agent(), config.user_agent);
+        assert!(config.proxy.is_none());
+        assert!(config.cache.is_some());
+        assert!(HttpClient::new(&config).unwrap().cache().is_some());
+
+        let proxied = HttpClientConfig {
+            proxy: Some("http://127.0.0.1:3128".to_string()),
+            pool_max_idle_per_host: 2,
+            ..Default::default()
+        };
+        assert!(HttpClient::new(&proxied).is_ok());
+    }
+
+    #[test]
+    fn init_test() {
+        let _ = shared();
+        assert_eq!(
+            ServiceErrorType::Failure,
+            init(HttpClientConfig::default()).unwrap_err().error_type
+        );
+    }
+}
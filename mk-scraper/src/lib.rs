@@ -2,91 +2,117 @@ pub mod cambridge_scraper;
 pub mod collins_scraper;
 pub mod oxford_scraper;
 pub mod unit_tests;
+pub mod audio;
+pub mod cache;
 pub mod constants;
+pub mod dictionary_scraper;
+pub mod export;
+pub mod fixtures;
+pub mod glossary;
+pub mod http_client;
+pub mod merger;
+pub mod migration;
 pub mod model;
+pub mod registry;
+pub mod scrape_report;
+pub mod scraper_config;
 pub mod task_executor;
+pub mod wiktionary;
 
-use std::io::Cursor;
+use std::path::Path;
 
 
-use common_libs::{error::{ServiceExuctionResult, ServiceError, ServiceErrorType, DictionaryError, DictionaryErrorKind}, files::read_file_content, utils::{trim_tabs, replace_tabs}};
-use constants::{USER_AGENT, INVALID, MP3_EXT};
+use common_libs::{error::{ServiceExuctionResult, ServiceError, ServiceErrorType, DictionaryError, DictionaryErrorKind}, utils::{trim_tabs, replace_tabs}};
+use constants::{INVALID, MP3_EXT};
 use itertools::Itertools;
 use log::{debug, error, info};
-use model::{DictionaryEntry, Dictionary};
-use reqwest::StatusCode;
+use http_client::{Fetcher, HttpClient};
+use model::DictionaryEntry;
+use scrape_report::{ScrapeProgress, ScrapeReport, MAX_CONCURRENT_SCRAPES};
 use scraper::{Html, Selector};
 
-use futures::future::join_all;
+use futures::{future::join_all, stream, StreamExt};
 use tokio::runtime;
 use crate::constants::NOT_FOUND;
 
 
-pub fn wget(url: &str, file_name: &str) -> ServiceExuctionResult<String> {
-    let status = std::process::Command::new("wget")
-    .arg("--user-agent")
-    .arg(USER_AGENT)
-    .arg(url)
-    .arg("-O")
-    .arg(file_name)
-    .status();
-
-    if status.is_err() {
-        error!("failed.executing.wget: {}", status.err().unwrap());
-        return Err(ServiceError {
-            message: "wget.failed".to_string(),
-            error_type: ServiceErrorType::Failure,
-        });
-    }
+/// Fallback for pages the plain client can't fetch: downloads through the
+/// browser-like client profile and keeps a copy in `file_name` for debugging.
+pub async fn download_as_browser(
+    fetcher: &dyn Fetcher,
+    url: &str,
+    file_name: &str,
+) -> ServiceExuctionResult<String> {
+    let content = save_as_browser(fetcher, url, file_name).await?;
+    check_not_found(&content)?;
+    Ok(content)
+}
+
+async fn save_as_browser(
+    fetcher: &dyn Fetcher,
+    url: &str,
+    file_name: &str,
+) -> ServiceExuctionResult<String> {
+    let content = match fetcher.browser_get_text(url).await {
+        Ok(content) => content,
+        Err(err) => {
+            error!("browser.download.failed: {} -> {}", url, err.message);
+            return Err(err);
+        }
+    };
 
-    let bytes = read_file_content(file_name)?;
-    Ok(String::from_utf8(bytes)?)
+    if let Some(dir) = Path::new(file_name).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if let Err(err) = std::fs::write(file_name, &content) {
+        error!("failed.to.save.html: {} -> {}", file_name, err);
+    }
+    Ok(content)
 }
 
 pub async fn fetch_url(url: String, file_name: String) -> ServiceExuctionResult<()> {
+    fetch_url_with(http_client::shared(), url, file_name).await
+}
+
+pub async fn fetch_url_with(
+    client: &HttpClient,
+    url: String,
+    file_name: String,
+) -> ServiceExuctionResult<()> {
     debug!("downloading from [{}] to {}", url, file_name);
-    let response = reqwest::get(url.clone()).await.unwrap();
-    let status = response.status();
-    info!("download status: {}", status);
-    if status.is_success() {
-        let mut file = std::fs::File::create(file_name.clone())?;
-        let mut content = Cursor::new(response.bytes().await?);
-        std::io::copy(&mut content, &mut file)?;
-        Ok(())
-    } else {
-        match wget(&url, &file_name) {
-            Ok(_) => Ok(()),
-            Err(err) => Err(err)
-        }
-    }
-    
+    // Raw bytes, also through the browser fallback: pronunciations are not text.
+    let content = client.get_bytes(&url).await?;
+    std::fs::write(&file_name, content)?;
+    info!("downloaded: {} -> {}", url, file_name);
+    Ok(())
 }
 
-async fn download_from_url(url: &str) -> ServiceExuctionResult<String> {
-    println!("URL: {}", url.clone());
-    let response = reqwest::get(url).await?;
-    println!("resposne: {:?}", response);
-    if StatusCode::OK != response.status() {
-        let msg = format!("nok.status.code: {}", response.status().as_str());
-        return Err(ServiceError {
-            message: msg,
-            error_type: ServiceErrorType::Unavailable,
-        });
-    }
+pub async fn download_from_url(url: &str) -> ServiceExuctionResult<String> {
+    download_with(http_client::default_fetcher(), url).await
+}
 
-    let bytes = response.bytes().await.unwrap();
-    let slice = &bytes[..];
-    let content = String::from_utf8(Vec::from(slice)).unwrap();
+async fn download_with(fetcher: &dyn Fetcher, url: &str) -> ServiceExuctionResult<String> {
+    debug!("URL: {}", url);
+    let content = fetcher.get_text(url).await?;
+    check_not_found(&content)?;
+    Ok(content)
+}
+
+fn check_not_found(content: &str) -> ServiceExuctionResult<()> {
+    check_not_found_with(content, &NOT_FOUND)
+}
+
+fn check_not_found_with(content: &str, markers: &[&str]) -> ServiceExuctionResult<()> {
     let lower = content.to_lowercase();
-    for i in 0..NOT_FOUND.len() {
-        if lower.contains(NOT_FOUND[i]) {
+    for marker in markers {
+        if lower.contains(marker) {
             return Err(ServiceError {
                 message: "not.found".to_string(),
                 error_type: ServiceErrorType::ResourceNotFound,
             });
         }
     }
-    Ok(content)
+    Ok(())
 }
 
 fn description(inner_html: String) -> Option<String> {
@@ -179,28 +205,24 @@ fn mp3_element(mp3_query: &str, html_content: &str) -> Option<String> {
     first_element(mp3_query, html_content, false)
 }
 
-pub async fn merge_definitions(url1: &str, url2: &str, url3: &str) -> Option<DictionaryEntry> {
-    let source_1 = oxford_scraper::download(url1).await;
-    let source_2 = oxford_scraper::download(url2).await;
-
-    if source_1.is_err() {
-        return None;
-    }
-
-    if source_2.is_err() {
-        return None;
-    }
+pub async fn merge_definitions(
+    fetcher: &dyn Fetcher,
+    url1: &str,
+    url2: &str,
+    url3: &str,
+) -> Option<DictionaryEntry> {
+    let mut merged = match oxford_scraper::download(fetcher, url1).await {
+        Ok(entry) => entry,
+        Err(_) => return None,
+    };
 
-    let mut merged = source_1.unwrap();
-    for def in source_2.unwrap().definitions {
-        merged.definitions.push(def);
-    }
-    if !url3.is_empty() {
-        let source_3 = oxford_scraper::download(url3).await;
-        if source_3.is_ok() {
-            for def in source_3.unwrap().definitions {
-                merged.definitions.push(def);
-            }
+    for url in [url2, url3] {
+        if url.is_empty() {
+            continue;
+        }
+        match oxford_scraper::download(fetcher, url).await {
+            Ok(entry) => merged.append(entry),
+            Err(err) => error!("merge.skipped: {} -> {}", url, err.message),
         }
     }
 
@@ -245,27 +267,100 @@ fn element_to_url(source: &str, start_with: &str, end_with: &str) -> Option<Stri
 }
 
 pub async fn scrape_it_from<S: AsRef<str>>(dictionary: String, word: S) -> Option<DictionaryEntry> {
+    scrape_it_with(http_client::default_fetcher(), dictionary, word).await
+}
+
+pub async fn scrape_it_with<S: AsRef<str>>(
+    fetcher: &dyn Fetcher,
+    dictionary: String,
+    word: S,
+) -> Option<DictionaryEntry> {
+    match scrape_word(fetcher, &dictionary, word.as_ref()).await {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            error!("resource.not.found: {} -> {}", word.as_ref(), err.message);
+            None
+        }
+    }
+}
+
+pub async fn scrape_word(
+    fetcher: &dyn Fetcher,
+    dictionary: &str,
+    word: &str,
+) -> ServiceExuctionResult<DictionaryEntry> {
     info!("scrape from {}", dictionary);
-    match Dictionary::from(dictionary) {
-        Dictionary::Cambridge => cambridge_scraper::scrape(word.as_ref()).await,
-        Dictionary::Collins => collins_scraper::scrape(word.as_ref()).await,
-        Dictionary::Oxford => oxford_scraper::scrape(word.as_ref()).await,
-        _ => return None,
+    match registry::lookup(dictionary) {
+        Some(scraper) => scraper.scrape(fetcher, word).await,
+        None => {
+            error!("dictionary.not.registered: {}", dictionary);
+            Err(ServiceError {
+                message: format!("dictionary.not.registered: {}", dictionary),
+                error_type: ServiceErrorType::Failure,
+            })
+        }
     }
 }
 
-pub  async fn scrape_all(source: Vec<String>) {
+/// Scrapes the word from all `merger::MERGED_DICTIONARIES` at once and merges
+/// what was found; None when no dictionary has it.
+pub async fn scrape_merged(fetcher: &dyn Fetcher, word: &str) -> Option<DictionaryEntry> {
+    let tasks = merger::MERGED_DICTIONARIES
+        .iter()
+        .map(|dictionary| scrape_it_with(fetcher, dictionary.to_string(), word));
+    let entries: Vec<DictionaryEntry> = join_all(tasks).await.into_iter().flatten().collect();
+    merger::merge(entries)
+}
+
+pub async fn scrape_all(source: Vec<String>) -> ScrapeReport {
+    let dictionaries: Vec<String> = merger::MERGED_DICTIONARIES.iter().map(|name| name.to_string()).collect();
+    scrape_all_with(
+        http_client::default_fetcher(),
+        source,
+        &dictionaries,
+        MAX_CONCURRENT_SCRAPES,
+        &|_| {},
+    )
+    .await
+}
+
+/// Scrapes every word from every dictionary, at most `concurrency` lookups at
+/// a time, and calls `progress` as each lookup completes.
+pub async fn scrape_all_with(
+    fetcher: &dyn Fetcher,
+    source: Vec<String>,
+    dictionaries: &[String],
+    concurrency: usize,
+    progress: &(dyn Fn(&ScrapeProgress) + Sync),
+) -> ScrapeReport {
     info!("scrape_all is being executed for {:?}", source);
-    let mut tasks = vec![];
-    for w in source {
-        let f1 = scrape_it_from("Cambridge".to_lowercase(), w.clone());
-        let f2 = scrape_it_from("Collins".to_lowercase(), w.clone());
-        let f3 = scrape_it_from("Oxford".to_lowercase(), w.clone());
-        tasks.push(f1);
-        tasks.push(f2);
-        tasks.push(f3);
-    }    
-    let _ = join_all(tasks);    
+    let mut report = ScrapeReport::new(source.clone());
+    let lookups: Vec<(String, String)> = source
+        .iter()
+        .flat_map(|word| dictionaries.iter().map(move |dictionary| (word.clone(), dictionary.clone())))
+        .collect();
+    let total = lookups.len();
+
+    let mut results = stream::iter(lookups)
+        .map(|(word, dictionary)| async move {
+            let result = scrape_word(fetcher, &dictionary, &word).await;
+            (word, dictionary, result)
+        })
+        .buffer_unordered(concurrency.max(1));
+    let mut done = 0;
+    while let Some((word, dictionary, result)) = results.next().await {
+        done += 1;
+        progress(&ScrapeProgress {
+            word: word.clone(),
+            dictionary: dictionary.clone(),
+            found: result.is_ok(),
+            done,
+            total,
+        });
+        report.add(&dictionary, &word, result);
+    }
+    info!("scrape_all: {}", report.summary());
+    report
 }
 
 pub  async fn tokio_scrape_all(source: Vec<String>) {
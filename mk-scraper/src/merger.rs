@@ -0,0 +1,240 @@
+use crate::model::{definition_similarity, Dictionary, DictionaryEntry, Pronunciation, Sense, SCHEMA_VERSION};
+
+/// Dictionaries combined by `scrape_merged`.
+pub const MERGED_DICTIONARIES: [&str; 3] = ["cambridge", "collins", "oxford"];
+/// Definitions at least this similar (see `definition_similarity`) are the same sense.
+pub const SIMILAR_DEFINITION: f64 = 0.8;
+
+/// Learner's dictionaries first: their definitions and audio win the ties.
+fn priority(source: &Dictionary) -> usize {
+    match source {
+        Dictionary::Oxford => 0,
+        Dictionary::Cambridge => 1,
+        Dictionary::Collins => 2,
+        Dictionary::Wiktionary => 4,
+        _ => 3,
+    }
+}
+
+/// Combines the entries of one word from several dictionaries. Near-identical
+/// senses are merged and keep all their `sources`; the senses and definitions
+/// are ranked best first, so the first ones are the ones worth showing.
+pub fn merge(mut entries: Vec<DictionaryEntry>) -> Option<DictionaryEntry> {
+    entries.sort_by_key(|entry| priority(&entry.source));
+    let first = entries.first()?;
+
+    let mut senses: Vec<Sense> = vec![];
+    for entry in &entries {
+        for sense in entry_senses(entry) {
+            match senses.iter_mut().find(|known| similar(known, &sense)) {
+                Some(known) => absorb(known, sense),
+                None => senses.push(sense),
+            }
+        }
+    }
+    senses.sort_by_key(|sense| {
+        (
+            std::cmp::Reverse(sense.sources.len()),
+            sense.examples.is_empty(),
+            sense.cefr.is_none(),
+        )
+    });
+
+    let uk = best_pronunciation(entries.iter().filter_map(|entry| entry.uk.as_ref()));
+    let us = best_pronunciation(entries.iter().filter_map(|entry| entry.us.as_ref()));
+    let mp3_link = uk
+        .as_ref()
+        .or(us.as_ref())
+        .and_then(|pronunciation| pronunciation.mp3_link.clone())
+        .or_else(|| entries.iter().find_map(|entry| entry.mp3_link.clone()));
+
+    Some(DictionaryEntry {
+        schema_version: SCHEMA_VERSION,
+        source: Dictionary::Merged,
+        url: first.url.clone(),
+        word: first.word.clone(),
+        mp3_link,
+        file: None,
+        definitions: senses.iter().map(|sense| sense.definition.clone()).collect(),
+        senses,
+        uk,
+        us,
+    })
+}
+
+/// The senses of an entry with their source; entries stored before senses
+/// existed only have definitions.
+fn entry_senses(entry: &DictionaryEntry) -> Vec<Sense> {
+    let senses = if entry.senses.is_empty() {
+        entry.definitions.iter().cloned().map(Sense::from).collect()
+    } else {
+        entry.senses.clone()
+    };
+    senses
+        .into_iter()
+        .map(|mut sense| {
+            if !sense.sources.contains(&entry.source) {
+                sense.sources.push(entry.source.clone());
+            }
+            sense
+        })
+        .collect()
+}
+
+fn similar(known: &Sense, sense: &Sense) -> bool {
+    if let (Some(known_pos), Some(pos)) = (&known.part_of_speech, &sense.part_of_speech) {
+        if !known_pos.eq_ignore_ascii_case(pos) {
+            return false;
+        }
+    }
+    definition_similarity(&known.definition, &sense.definition) >= SIMILAR_DEFINITION
+}
+
+/// Keeps the known definition and takes what it lacks from the other one.
+fn absorb(known: &mut Sense, sense: Sense) {
+    for source in sense.sources {
+        if !known.sources.contains(&source) {
+            known.sources.push(source);
+        }
+    }
+    for example in sense.examples {
+        if !known.examples.contains(&example) {
+            known.examples.push(example);
+        }
+    }
+    for domain in sense.domains {
+        if !known.domains.contains(&domain) {
+            known.domains.push(domain);
+        }
+    }
+    if known.part_of_speech.is_none() {
+        known.part_of_speech = sense.part_of_speech;
+    }
+    if known.grammar.is_none() {
+        known.grammar = sense.grammar;
+    }
+    if known.cefr.is_none() {
+        known.cefr = sense.cefr;
+    }
+    if known.region.is_none() {
+        known.region = sense.region;
+    }
+}
+
+/// The first pronunciation with audio, completed with the first IPA found.
+fn best_pronunciation<'a>(pronunciations: impl Iterator<Item = &'a Pronunciation> + Clone) -> Option<Pronunciation> {
+    let ipa = pronunciations.clone().find_map(|pronunciation| pronunciation.ipa.clone());
+    let mut best = pronunciations
+        .clone()
+        .find(|pronunciation| pronunciation.mp3_link.is_some())
+        .or_else(|| pronunciations.clone().next())?
+        .clone();
+    if best.ipa.is_none() {
+        best.ipa = ipa;
+    }
+    Some(best)
+}
+
+#[cfg(test)]
+mod merger_tests {
+    use crate::{
+        fixtures::{FixtureFetcher, FIXTURES_DIR},
+        model::{definition_similarity, Dictionary, DictionaryEntry, Pronunciation, Sense},
+        scrape_merged,
+    };
+
+    use super::merge;
+
+    fn entry(source: Dictionary, senses: Vec<Sense>, uk: Option<Pronunciation>) -> DictionaryEntry {
+        DictionaryEntry {
+            url: format!("https://{}.test/rampage", source.name()),
+            mp3_link: uk.as_ref().and_then(|uk| uk.mp3_link.clone()),
+            definitions: senses.iter().map(|sense| sense.definition.clone()).collect(),
+            senses,
+            uk,
+            ..DictionaryEntry::new(source, "rampage")
+        }
+    }
+
+    fn sense(definition: &str, part_of_speech: &str, examples: Vec<&str>) -> Sense {
+        Sense {
+            definition: definition.to_string(),
+            part_of_speech: Some(part_of_speech.to_string()),
+            examples: examples.into_iter().map(|example| example.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn similarity_test() {
+        assert_eq!(1.0, definition_similarity("A violent, wild rush.", "a violent wild rush"));
+        assert_eq!(0.0, definition_similarity("violent rush", "calm walk"));
+        assert!(definition_similarity("to rush about violently", "to rush about wildly and violently") >= 0.8);
+    }
+
+    #[test]
+    fn merge_test() {
+        assert!(merge(vec![]).is_none());
+
+        let collins = entry(
+            Dictionary::Collins,
+            vec![
+                sense("To rush about wildly and violently.", "verb", vec![]),
+                sense("A period of wild, violent behaviour.", "noun", vec!["The mob went on a rampage."]),
+            ],
+            Some(Pronunciation {
+                ipa: Some("ræmˈpeɪdʒ".to_string()),
+                mp3_link: Some("https://collins.test/rampage.mp3".to_string()),
+            }),
+        );
+        let cambridge = entry(
+            Dictionary::Cambridge,
+            vec![
+                sense("to rush about violently", "noun", vec![]),
+                sense("to rush about wildly and violently", "verb", vec!["Elephants rampaged through the village."]),
+            ],
+            Some(Pronunciation {
+                ipa: None,
+                mp3_link: Some("https://cambridge.test/rampage.mp3".to_string()),
+            }),
+        );
+        let mut old = entry(Dictionary::Oxford, vec![], None);
+        old.definitions = vec!["A period of wild, violent behaviour".to_string()];
+
+        let merged = merge(vec![collins, cambridge, old]).unwrap();
+        assert_eq!(Dictionary::Merged, merged.source);
+        assert_eq!("https://oxford.test/rampage", merged.url);
+        assert_eq!(3, merged.senses.len());
+        assert_eq!(merged.definitions.len(), merged.senses.len());
+
+        let best = &merged.senses[0];
+        assert_eq!("A period of wild, violent behaviour", best.definition);
+        assert_eq!(vec![Dictionary::Oxford, Dictionary::Collins], best.sources);
+        assert_eq!(Some("noun".to_string()), best.part_of_speech);
+        assert_eq!(vec!["The mob went on a rampage."], best.examples);
+
+        let verb = &merged.senses[1];
+        assert_eq!(vec![Dictionary::Cambridge, Dictionary::Collins], verb.sources);
+        assert_eq!(vec![Dictionary::Cambridge], merged.senses[2].sources);
+
+        let uk = merged.uk.unwrap();
+        assert_eq!(Some("https://cambridge.test/rampage.mp3".to_string()), uk.mp3_link);
+        assert_eq!(Some("ræmˈpeɪdʒ".to_string()), uk.ipa);
+        assert_eq!(uk.mp3_link, merged.mp3_link);
+    }
+
+    #[tokio::test]
+    async fn scrape_merged_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let merged = scrape_merged(&fixtures, "correct").await.unwrap();
+        assert_eq!("correct", merged.word);
+        assert!(merged
+            .senses
+            .iter()
+            .all(|sense| sense.sources.contains(&Dictionary::Oxford) || sense.sources.contains(&Dictionary::Cambridge)));
+        assert!(merged.senses.iter().all(|sense| !sense.sources.contains(&Dictionary::Collins)));
+        assert!(merged.uk.is_some() && merged.us.is_some());
+
+        assert!(scrape_merged(&fixtures, "amisega").await.is_none());
+    }
+}
@@ -0,0 +1,288 @@
+use std::{fs, path::Path};
+
+use common_libs::error::{ServiceError, ServiceErrorType, ServiceExuctionResult};
+use log::{error, info};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    constants::JSON_EXT,
+    model::{SCHEMA_VERSION, UNVERSIONED},
+};
+
+pub const DATA_DIR: &str = "data/en-en";
+const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationReport {
+    pub scanned: usize,
+    pub migrated: Vec<String>,
+    pub up_to_date: usize,
+    /// File name and reason.
+    pub failed: Vec<(String, String)>,
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} files scanned, {} {}, {} up to date, {} failed",
+            self.scanned,
+            self.migrated.len(),
+            if self.dry_run { "to migrate" } else { "migrated" },
+            self.up_to_date,
+            self.failed.len()
+        )
+    }
+}
+
+pub fn schema_version(entry: &Value) -> u32 {
+    entry
+        .get(SCHEMA_VERSION_FIELD)
+        .and_then(|version| version.as_u64())
+        .map(|version| version as u32)
+        .unwrap_or(UNVERSIONED)
+}
+
+/// Upgrades one stored entry to `SCHEMA_VERSION`, one version at a time.
+/// Returns false when the entry is already current.
+pub fn migrate(entry: &mut Value) -> ServiceExuctionResult<bool> {
+    let from = schema_version(entry);
+    let fields = match entry.as_object_mut() {
+        Some(fields) => fields,
+        None => return Err(invalid("entry.is.not.an.object")),
+    };
+
+    if from > SCHEMA_VERSION {
+        return Err(invalid(&format!("schema.version.not.supported: {}", from)));
+    }
+
+    for version in from..SCHEMA_VERSION {
+        match version {
+            1 => to_v2(fields)?,
+            2 => to_v3(fields)?,
+            _ => return Err(invalid(&format!("no.migration.from: {}", version))),
+        }
+        fields.insert(SCHEMA_VERSION_FIELD.to_string(), json!(version + 1));
+    }
+    Ok(from < SCHEMA_VERSION)
+}
+
+/// v2 adds structured senses (one per definition) and the UK/US pronunciations;
+/// the old single `mp3_link` is the UK one for all three dictionaries.
+fn to_v2(fields: &mut Map<String, Value>) -> ServiceExuctionResult<()> {
+    for required in ["source", "url", "word", "definitions"] {
+        if !fields.contains_key(required) {
+            return Err(invalid(&format!("missing.field: {}", required)));
+        }
+    }
+
+    let senses: Vec<Value> = fields["definitions"]
+        .as_array()
+        .ok_or_else(|| invalid("definitions.is.not.an.array"))?
+        .iter()
+        .map(|definition| {
+            json!({
+                "definition": definition,
+                "part_of_speech": null,
+                "grammar": null,
+                "examples": [],
+                "cefr": null,
+                "domains": [],
+            })
+        })
+        .collect();
+    fields.entry("senses").or_insert(Value::Array(senses));
+
+    let uk = match fields.get("mp3_link") {
+        Some(Value::String(mp3_link)) => json!({ "ipa": null, "mp3_link": mp3_link }),
+        _ => Value::Null,
+    };
+    fields.entry("mp3_link").or_insert(Value::Null);
+    fields.entry("file").or_insert(Value::Null);
+    fields.entry("uk").or_insert(uk);
+    fields.entry("us").or_insert(Value::Null);
+    Ok(())
+}
+
+/// v3 takes the source from the page address (Collins entries used to be
+/// stored as Cambridge) and adds the `region` of the senses.
+fn to_v3(fields: &mut Map<String, Value>) -> ServiceExuctionResult<()> {
+    let url = fields.get("url").and_then(|url| url.as_str()).unwrap_or_default();
+    let source = [
+        ("collinsdictionary.com", "Collins"),
+        ("cambridge.org", "Cambridge"),
+        ("oxfordlearnersdictionaries.com", "Oxford"),
+    ]
+    .into_iter()
+    .find(|(host, _)| url.contains(host))
+    .map(|(_, source)| source);
+    if let Some(source) = source {
+        fields.insert("source".to_string(), json!(source));
+    }
+
+    if let Some(Value::Array(senses)) = fields.get_mut("senses") {
+        for sense in senses.iter_mut().filter_map(|sense| sense.as_object_mut()) {
+            sense.entry("region").or_insert(Value::Null);
+        }
+    }
+    Ok(())
+}
+
+/// Migrates one file in place; nothing is written on a dry run.
+pub fn migrate_file(file_name: &str, dry_run: bool) -> ServiceExuctionResult<bool> {
+    let content = fs::read_to_string(file_name)?;
+    let mut entry: Value = serde_json::from_str(&content)
+        .map_err(|err| invalid(&format!("invalid.json: {}", err)))?;
+
+    if !migrate(&mut entry)? {
+        return Ok(false);
+    }
+
+    if !dry_run {
+        let migrated = serde_json::to_string_pretty(&entry)
+            .map_err(|err| invalid(&format!("invalid.json: {}", err)))?;
+        let tmp_file = [file_name, ".tmp"].join("");
+        fs::write(&tmp_file, migrated)?;
+        fs::rename(&tmp_file, file_name)?;
+    }
+    Ok(true)
+}
+
+/// Migrates every `.json` file under `dir` (e.g. `data/en-en/<dictionary>/json/`).
+pub fn migrate_dir(dir: &str, dry_run: bool) -> ServiceExuctionResult<MigrationReport> {
+    if !Path::new(dir).is_dir() {
+        return Err(ServiceError {
+            message: format!("dir.not.found: {}", dir),
+            error_type: ServiceErrorType::ResourceNotFound,
+        });
+    }
+
+    let mut report = MigrationReport {
+        dry_run,
+        ..Default::default()
+    };
+    for file_name in json_files(Path::new(dir))? {
+        report.scanned += 1;
+        match migrate_file(&file_name, dry_run) {
+            Ok(true) => report.migrated.push(file_name),
+            Ok(false) => report.up_to_date += 1,
+            Err(err) => {
+                error!("migration.failed: {} -> {}", file_name, err.message);
+                report.failed.push((file_name, err.message));
+            }
+        }
+    }
+    info!("{}: {}", dir, report.summary());
+    Ok(report)
+}
+
+fn json_files(dir: &Path) -> ServiceExuctionResult<Vec<String>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(json_files(&path)?);
+        } else if path.to_string_lossy().ends_with(JSON_EXT) {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn invalid(message: &str) -> ServiceError {
+    ServiceError {
+        message: message.to_string(),
+        error_type: ServiceErrorType::Failure,
+    }
+}
+
+#[cfg(test)]
+mod migration_tests {
+    use std::fs;
+
+    use common_libs::files::temp_dir;
+    use serde_json::{json, Value};
+
+    use crate::model::{Dictionary, DictionaryEntry, Sense, SCHEMA_VERSION, UNVERSIONED};
+
+    use super::{migrate, migrate_dir, schema_version};
+
+    fn unversioned() -> Value {
+        json!({
+            "source": "Cambridge",
+            "url": "https://dictionary.cambridge.org/dictionary/english/emerging",
+            "word": "emerging",
+            "mp3_link": "https://dictionary.cambridge.org/media/english/uk_pron/u/uke/ukemb/ukembry011.mp3",
+            "file": null,
+            "definitions": ["starting to exist:", "just beginning to exist:"]
+        })
+    }
+
+    #[test]
+    fn migrate_test() {
+        let mut entry = unversioned();
+        assert_eq!(UNVERSIONED, schema_version(&entry));
+        assert!(migrate(&mut entry).unwrap());
+        assert_eq!(SCHEMA_VERSION, schema_version(&entry));
+        assert!(!migrate(&mut entry).unwrap());
+
+        let old: DictionaryEntry = serde_json::from_value(unversioned()).unwrap();
+        assert_eq!(UNVERSIONED, old.schema_version);
+        assert!(old.senses.is_empty());
+
+        let entry: DictionaryEntry = serde_json::from_value(entry).unwrap();
+        assert_eq!(SCHEMA_VERSION, entry.schema_version);
+        assert_eq!(2, entry.senses.len());
+        assert_eq!("starting to exist:", entry.senses[0].definition);
+        assert_eq!(entry.mp3_link, entry.uk.unwrap().mp3_link);
+        assert!(entry.us.is_none());
+
+        let mut collins = json!({
+            "schema_version": 2,
+            "source": "Cambridge",
+            "url": "https://www.collinsdictionary.com/dictionary/english/dust-up",
+            "word": "dust up",
+            "definitions": ["a quarrel, fight, or argument"],
+            "senses": [{ "definition": "a quarrel, fight, or argument", "examples": [], "domains": [] }]
+        });
+        assert!(migrate(&mut collins).unwrap());
+        let collins: DictionaryEntry = serde_json::from_value(collins).unwrap();
+        assert!(matches!(collins.source, Dictionary::Collins));
+        assert!(collins.senses[0].region.is_none());
+        let sense: Sense = serde_json::from_value(json!({ "definition": "a quarrel" })).unwrap();
+        assert!(sense.examples.is_empty() && sense.domains.is_empty());
+
+        let mut newer = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(migrate(&mut newer).is_err());
+        let mut broken = json!({ "word": "emerging" });
+        assert!(migrate(&mut broken).is_err());
+    }
+
+    #[test]
+    fn migrate_dir_test() {
+        let dir = temp_dir("mk_scraper_migration_tests");
+        let json_dir = std::path::Path::new(&dir).join("cambridge").join("json");
+        fs::create_dir_all(&json_dir).unwrap();
+        let old = json_dir.join("emerging.json");
+        fs::write(&old, serde_json::to_string_pretty(&unversioned()).unwrap()).unwrap();
+        fs::write(json_dir.join("broken.json"), "{ not json").unwrap();
+        fs::write(json_dir.join("notes.txt"), "ignored").unwrap();
+
+        let dry_run = migrate_dir(&dir, true).unwrap();
+        assert_eq!(2, dry_run.scanned);
+        assert_eq!(1, dry_run.migrated.len());
+        assert_eq!(1, dry_run.failed.len());
+        let content: Value = serde_json::from_str(&fs::read_to_string(&old).unwrap()).unwrap();
+        assert_eq!(unversioned(), content);
+
+        let report = migrate_dir(&dir, false).unwrap();
+        assert_eq!(dry_run.migrated, report.migrated);
+        let content: Value = serde_json::from_str(&fs::read_to_string(&old).unwrap()).unwrap();
+        assert_eq!(SCHEMA_VERSION, schema_version(&content));
+
+        let again = migrate_dir(&dir, false).unwrap();
+        assert!(again.migrated.is_empty());
+        assert_eq!(1, again.up_to_date);
+    }
+}
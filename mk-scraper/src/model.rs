@@ -1,10 +1,25 @@
+use std::collections::HashSet;
+
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::registry;
+
+/// Version of the stored `DictionaryEntry` JSON; see `migration` for the upgrades.
+pub const SCHEMA_VERSION: u32 = 3;
+/// Files written before `schema_version` existed.
+pub const UNVERSIONED: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Dictionary {
     Collins,
     Cambridge,
     Oxford,
+    /// The offline Wiktionary extract loaded by `wiktionary::register`.
+    Wiktionary,
+    /// Several dictionaries combined by `merger::merge`.
+    Merged,
+    /// A dictionary added at runtime through `registry::register`.
+    Registered(String),
     Undefined,
 }
 
@@ -14,17 +29,172 @@ impl From<String> for Dictionary {
             "cambridge" => Dictionary::Cambridge,
             "collins" => Dictionary::Collins,
             "oxford" => Dictionary::Oxford,
+            "wiktionary" => Dictionary::Wiktionary,
+            "merged" => Dictionary::Merged,
+            name if registry::contains(name) => Dictionary::Registered(name.to_string()),
             _ => Dictionary::Undefined,
         }
     }
 }
 
+impl Dictionary {
+    /// The registry name of the dictionary.
+    pub fn name(&self) -> String {
+        match self {
+            Dictionary::Collins => "collins".to_string(),
+            Dictionary::Cambridge => "cambridge".to_string(),
+            Dictionary::Oxford => "oxford".to_string(),
+            Dictionary::Wiktionary => "wiktionary".to_string(),
+            Dictionary::Merged => "merged".to_string(),
+            Dictionary::Registered(name) => name.clone(),
+            Dictionary::Undefined => "undefined".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DictionaryEntry {
+    #[serde(default = "unversioned")]
+    pub schema_version: u32,
     pub source: Dictionary,
     pub url: String,
     pub word: String,
     pub mp3_link: Option<String>,
     pub file: Option<String>,
     pub definitions: Vec<String>,
+    #[serde(default)]
+    pub senses: Vec<Sense>,
+    #[serde(default)]
+    pub uk: Option<Pronunciation>,
+    #[serde(default)]
+    pub us: Option<Pronunciation>,
+}
+
+fn unversioned() -> u32 {
+    UNVERSIONED
+}
+
+impl DictionaryEntry {
+    /// An empty entry of the current schema, to be filled in by the caller.
+    pub fn new(source: Dictionary, word: &str) -> Self {
+        DictionaryEntry {
+            schema_version: SCHEMA_VERSION,
+            source,
+            url: String::new(),
+            word: word.to_string(),
+            mp3_link: None,
+            file: None,
+            definitions: vec![],
+            senses: vec![],
+            uk: None,
+            us: None,
+        }
+    }
+
+    /// Parts of speech of all senses, in order of appearance.
+    pub fn parts_of_speech(&self) -> Vec<String> {
+        let mut found: Vec<String> = vec![];
+        for pos in self.senses.iter().filter_map(|sense| sense.part_of_speech.clone()) {
+            if !found.contains(&pos) {
+                found.push(pos);
+            }
+        }
+        found
+    }
+
+    /// Adds the definitions and senses of another page of the same word,
+    /// skipping the ones already known.
+    pub fn append(&mut self, other: DictionaryEntry) {
+        for definition in other.definitions {
+            if !self.definitions.iter().any(|known| same_definition(known, &definition)) {
+                self.definitions.push(definition);
+            }
+        }
+        for sense in other.senses {
+            if !self.senses.iter().any(|known| same_definition(&known.definition, &sense.definition)) {
+                self.senses.push(sense);
+            }
+        }
+        if self.uk.is_none() {
+            self.uk = other.uk;
+        }
+        if self.us.is_none() {
+            self.us = other.us;
+        }
+    }
+
+    pub fn examples(&self) -> Vec<String> {
+        self.senses.iter().flat_map(|sense| sense.examples.clone()).collect()
+    }
+}
+
+/// Case, spacing and trailing punctuation aside, the same text.
+pub fn same_definition(first: &str, second: &str) -> bool {
+    normalize_definition(first) == normalize_definition(second)
+}
+
+/// Share of the words the two definitions have in common (Dice coefficient):
+/// 1.0 for the same text, 0.0 for no word in common.
+pub fn definition_similarity(first: &str, second: &str) -> f64 {
+    let first = definition_words(first);
+    let second = definition_words(second);
+    if first.is_empty() && second.is_empty() {
+        return 1.0;
+    }
+    let common = first.intersection(&second).count();
+    2.0 * common as f64 / (first.len() + second.len()) as f64
+}
+
+fn definition_words(definition: &str) -> HashSet<String> {
+    definition
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn normalize_definition(definition: &str) -> String {
+    definition
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(|c: char| ":;,. ".contains(c))
+        .to_lowercase()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Sense {
+    pub definition: String,
+    /// e.g. `verb`, `noun`
+    pub part_of_speech: Option<String>,
+    /// Grammar codes such as `[transitive]` or `(intransitive)`.
+    pub grammar: Option<String>,
+    #[serde(default)]
+    pub examples: Vec<String>,
+    /// CEFR level in upper case (`A1` .. `C2`).
+    pub cefr: Option<String>,
+    /// Subject and usage labels, e.g. `medical`, `formal`.
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Variety of English for dictionaries with separate sections, e.g. `British`.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Dictionaries giving this sense, filled in when entries are merged.
+    #[serde(default)]
+    pub sources: Vec<Dictionary>,
+}
+
+impl From<String> for Sense {
+    fn from(definition: String) -> Self {
+        Sense {
+            definition,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Pronunciation {
+    pub ipa: Option<String>,
+    pub mp3_link: Option<String>,
 }
\ No newline at end of file
@@ -1,69 +1,98 @@
-use common_libs::{utils::{trim_tabs, replace_tabs}, error::{ServiceExuctionResult, ServiceError, ServiceErrorType}};
+use common_libs::error::ServiceExuctionResult;
 use log::error;
 
 
-use crate::{first_element, model::{DictionaryEntry, Dictionary}};
-
-use super::{
-    description, download_from_url, elements, merge_definitions, mp3_element, mp3_element_to_url,
-    to_url,
+use crate::{
+    dictionary_scraper::{DictionaryScraper, PronunciationQueries, SenseQueries},
+    first_element,
+    http_client::{self, Fetcher},
+    model::{DictionaryEntry, Dictionary},
 };
 
+use super::{download_with, to_url};
+
 const URL: &str = r#"https://www.oxfordlearnersdictionaries.com/definition/english/"#;
 pub const MP3_QUERY: &str = r#"div[class="sound audio_play_button pron-uk icon-audio"]"#;
 pub const MP3_START_WITH: &str = r#"https://"#;
 pub const DEFINITION_QUERY: &str = r#"div[class="entry"] span[class="def"]"#;
+pub const HEADWORD_QUERY: &str = r#"div[class="webtop"] h1[class="headword"]"#;
 
-fn mp3_url(html: &str) -> Option<String> {
-    let element = mp3_element(MP3_QUERY, &html);
+pub struct OxfordScraper;
 
-    if element.is_none() {
-        return None;
+impl DictionaryScraper for OxfordScraper {
+    fn name(&self) -> &str {
+        "oxford"
     }
 
-    mp3_element_to_url(&element.unwrap(), MP3_START_WITH)
-}
+    fn dictionary(&self) -> Dictionary {
+        Dictionary::Oxford
+    }
 
-fn to_definitions(html: &str) -> Vec<String> {
-    let definitions = elements(DEFINITION_QUERY, &html, true);
-    definitions
-        .into_iter()
-        .map(|e| description(e))
-        .filter(Option::is_some)
-        .map(Option::unwrap)
-        .collect()
-}
+    fn base_url(&self) -> &str {
+        URL
+    }
 
-pub fn check_for_more(word: &str, html: &str) -> Option<String> {
-    let first = first_element(r#"link[rel="canonical"]"#, &html, false);
+    fn definition_query(&self) -> &str {
+        DEFINITION_QUERY
+    }
 
-    if first.is_none() {
-        return None;
+    fn mp3_query(&self) -> &str {
+        MP3_QUERY
     }
 
-    let base_url = to_url(URL, word);
+    fn mp3_start_with(&self) -> &str {
+        MP3_START_WITH
+    }
 
-    let canonical = first.unwrap();
-    let mut expected_url = String::from(r#"""#);
-    expected_url.push_str(&base_url);
-    expected_url.push_str(r#"""#);
+    fn headword_queries(&self) -> Vec<&str> {
+        vec![HEADWORD_QUERY]
+    }
 
-    if canonical.contains(&expected_url) {
-        return None;
+    /// Words with several entries (e.g. noun and verb) redirect to the first
+    /// numbered page; all of them are merged into one entry.
+    fn numbered_senses(&self) -> bool {
+        true
     }
 
-    for i in 1..4 {
-        let mut search_url = String::from(&base_url);
-        let id = vec![r#"_"#, &i.to_string()].join("");
-        search_url.push_str(&id);
+    fn sense_queries(&self) -> Option<SenseQueries> {
+        Some(SenseQueries {
+            block: Some(r#"div.entry"#.to_string()),
+            part_of_speech: Some(r#"div.webtop span.pos"#.to_string()),
+            sense: r#"li.sense"#.to_string(),
+            definition: r#"span.def"#.to_string(),
+            grammar: Some(r#"span.grammar"#.to_string()),
+            examples: Some(r#"ul.examples span.x"#.to_string()),
+            cefr: Some(r#"@cefr"#.to_string()),
+            domains: Some(r#"span.labels"#.to_string()),
+            exclude: None,
+            regions: vec![],
+        })
+    }
 
-        if canonical.contains(&search_url) {
-            let next_id = vec![r#"_"#, &(i + 1).to_string()].join("");
-            search_url = search_url.replace(&id, &next_id);
-            return Some(search_url);
+    fn pronunciation_queries(&self) -> PronunciationQueries {
+        PronunciationQueries {
+            uk_ipa: Some(r#"div.phons_br span.phon"#.to_string()),
+            uk_mp3: Some(r#"div.phons_br div.sound"#.to_string()),
+            us_ipa: Some(r#"div.phons_n_am span.phon"#.to_string()),
+            us_mp3: Some(r#"div.phons_n_am div.sound"#.to_string()),
         }
     }
-    return None;
+}
+
+/// The next numbered page after the one the canonical link points to, e.g.
+/// `correct_3` for a page whose canonical URL is `correct_2`.
+pub fn check_for_more(word: &str, html: &str) -> Option<String> {
+    let canonical = first_element(r#"link[rel="canonical"]"#, html, false)?;
+    let base_url = to_url(URL, word);
+    let prefix = [base_url.as_str(), "_"].join("");
+    let start = canonical.find(&prefix)? + prefix.len();
+    let page: usize = canonical[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()?;
+    Some([base_url, (page + 1).to_string()].join("_"))
 }
 
 pub fn check_for_redirects(word: &str, html: &str) -> bool {
@@ -80,74 +109,31 @@ pub fn check_for_redirects(word: &str, html: &str) -> bool {
 }
 
 pub async fn scrape(word: &str) -> Option<DictionaryEntry> {
-    if word.trim().is_empty() || word.trim().len() < 3 {
-        error!("word.min.lenght.is.3");
-        return None;
-    }
-    let mut trimmed = trim_tabs(&mut String::from(word));
-    trimmed = replace_tabs(&mut trimmed, "-");
-    let url = to_url(URL, &trimmed);
-    let html = match download_from_url(&url).await {
-        Ok(content) => content,
-        Err(_) => return None,
-    };
-
-    if check_for_redirects(word, &html) {
-        merge_definitions(
-            vec![URL, &trimmed, r#"_1"#].join("").as_str(),
-            vec![URL, &trimmed, r#"_2"#].join("").as_str(),
-            vec![URL, &trimmed, r#"_3"#].join("").as_str(),
-        )
-        .await
-    } else {
-        let mp3_link = mp3_url(&html);
-        let definitions = to_definitions(&html);
+    scrape_with(http_client::default_fetcher(), word).await
+}
 
-        if mp3_link.is_none() || definitions.is_empty() {
-            return None;
+pub async fn scrape_with(fetcher: &dyn Fetcher, word: &str) -> Option<DictionaryEntry> {
+    match OxfordScraper.scrape(fetcher, word).await {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            error!("resource.not.found: {} -> {}", word, err.message);
+            None
         }
-
-        Some(DictionaryEntry {
-            source: Dictionary::Oxford,
-            word: word.clone().to_lowercase(),
-            url: to_url(URL, word),
-            mp3_link,
-            definitions,
-            file: None,
-        })
     }
 }
 
-pub async fn download(url: &str) -> ServiceExuctionResult<DictionaryEntry> {
-    let html = (download_from_url(url).await)?;
-
-    let mp3_link = mp3_url(&html);
-    let definitions = to_definitions(&html);
-
-    if mp3_link.is_none() || definitions.is_empty() {
-        return Err(ServiceError {
-            message: "definitions or/and mp3 files are not found".to_string(),
-            error_type: ServiceErrorType::ResourceNotFound,
-        });
-    }
-
-    let found = first_element(r#"div[class="webtop"] h1[class="headword"]"#, &html, true);
-    let word = found.unwrap();
-    Ok(DictionaryEntry {
-        source: Dictionary::Oxford,
-        word: word.clone(),
-        url: to_url(URL, &word),
-        mp3_link,
-        definitions,
-        file: None,
-    })
+/// Downloads a single entry page, e.g. one of the numbered senses.
+pub async fn download(fetcher: &dyn Fetcher, url: &str) -> ServiceExuctionResult<DictionaryEntry> {
+    let html = (download_with(fetcher, url).await)?;
+    let word = url.rsplit('/').next().unwrap_or_default();
+    OxfordScraper.parse(word, &html)
 }
 
 #[cfg(test)]
 mod oxford_unit_tests {
-    use common_libs::files::{file_name, read_file_content};
+    use common_libs::{files::{file_name, read_file_content, temp_dir}};
 
-    use crate::{oxford_scraper::{self, check_for_more}, merge_definitions, constants::HTML_EXT, download_from_url};
+    use crate::{dictionary_scraper::DictionaryScraper, oxford_scraper::{self, check_for_more, OxfordScraper}, merge_definitions, constants::HTML_EXT, download_from_url, http_client, fixtures::{FixtureFetcher, FIXTURES_DIR}, download_with, cache::key};
 
     
     #[tokio::test]
@@ -161,8 +147,10 @@ mod oxford_unit_tests {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn scrape_wind_up_test() {
         let res = merge_definitions(
+            http_client::default_fetcher(),
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/wind-up_1"#,
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/wind-up_2"#,
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/wind-up_3"#,
@@ -183,8 +171,8 @@ mod oxford_unit_tests {
 
     #[tokio::test]
     async fn to_next_word3_test() {
-        let word = "correct_2";
-        let file_name = file_name("download/tmp/oxford/", word, HTML_EXT);
+        let url = r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#;
+        let file_name = file_name(FIXTURES_DIR, &key(url), HTML_EXT);
         let html = match read_file_content(&file_name) {
             Ok(cnt) => {
                 println!("size: {}", cnt.len());
@@ -227,6 +215,7 @@ mod oxford_unit_tests {
         println!("found: {:?}", correct.unwrap());
 
         let correct = oxford_scraper::download(
+            http_client::default_fetcher(),
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_1"#,
         )
         .await;
@@ -234,10 +223,129 @@ mod oxford_unit_tests {
         println!("found: {:?}", correct.unwrap());
 
         let correct = oxford_scraper::download(
+            http_client::default_fetcher(),
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#,
         )
         .await;
         assert!(correct.is_ok());
         println!("found: {:?}", correct.unwrap());
     }
+
+    #[tokio::test]
+    async fn oxford_replay_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let html = download_with(
+            &fixtures,
+            r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            Some(String::from(
+                r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_3"#
+            )),
+            check_for_more("correct", &html)
+        );
+
+        let merged = merge_definitions(
+            &fixtures,
+            r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_1"#,
+            r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#,
+            r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_3"#,
+        )
+        .await;
+        assert!(merged.is_some());
+        let merged = merged.unwrap();
+        let first = oxford_scraper::download(
+            &fixtures,
+            r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_1"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.definitions.len() + 3, merged.definitions.len());
+
+        let scraped = oxford_scraper::scrape_with(&fixtures, "correct").await.unwrap();
+        assert_eq!(merged.definitions, scraped.definitions);
+
+        assert_eq!(merged.senses, scraped.senses);
+        assert_eq!(vec!["adjective", "verb"], scraped.parts_of_speech());
+        let verb = scraped.senses.iter().find(|sense| sense.cefr == Some("B1".to_string())).unwrap();
+        assert_eq!(Some("verb".to_string()), verb.part_of_speech);
+        assert_eq!(Some("[transitive]".to_string()), verb.grammar);
+        assert_eq!(2, verb.examples.len());
+        assert_eq!(Some("kəˈrekt".to_string()), scraped.uk.unwrap().ipa);
+        assert!(scraped.us.unwrap().mp3_link.unwrap().ends_with("correct__us_1.mp3"));
+    }
+
+    fn sense_page(page: usize, pos: &str, definitions: &[&str]) -> String {
+        let senses: Vec<String> = definitions
+            .iter()
+            .map(|definition| format!(r#"<li class="sense"><span class="def">{}</span></li>"#, definition))
+            .collect();
+        format!(
+            r#"<html><head><link rel="canonical" href="https://www.oxfordlearnersdictionaries.com/definition/english/present_{}" /></head>
+<body><div class="entry"><div class="webtop"><h1 class="headword">present</h1> <span class="pos">{}</span>
+<div class="sound audio_play_button pron-uk icon-audio" data-src-mp3="https://www.oxfordlearnersdictionaries.com/media/english/uk_pron/p/pre/prese/present__gb_{}.mp3"></div></div>
+<ol>{}</ol></div></body></html>"#,
+            page,
+            pos,
+            page,
+            senses.join("")
+        )
+    }
+
+    #[tokio::test]
+    async fn sense_pages_test() {
+        let dir = temp_dir("mk_scraper_sense_pages_tests");
+        let url = "https://www.oxfordlearnersdictionaries.com/definition/english/present";
+        let pages = [
+            sense_page(1, "adjective", &["being in a particular place", "existing now"]),
+            sense_page(2, "noun", &["a thing that you give to somebody"]),
+            sense_page(3, "verb", &["to give something to somebody"]),
+            sense_page(4, "verb", &["To give something to somebody.", "to show or offer something"]),
+        ];
+        std::fs::write(format!("{}/{}.html", dir, key(url)), &pages[0]).unwrap();
+        for (page, html) in pages.iter().enumerate() {
+            std::fs::write(format!("{}/{}_{}.html", dir, key(url), page + 1), html).unwrap();
+        }
+        std::fs::write(format!("{}/{}_5.notfound", dir, key(url)), url).unwrap();
+
+        assert_eq!(Some(format!("{}_5", url)), check_for_more("present", &pages[3]));
+
+        let fixtures = FixtureFetcher::replay(&dir);
+        let entry = oxford_scraper::scrape_with(&fixtures, "present").await.unwrap();
+        assert_eq!(5, entry.definitions.len());
+        assert_eq!(5, entry.senses.len());
+        assert_eq!(vec!["adjective", "noun", "verb"], entry.parts_of_speech());
+        assert_eq!(Some("noun".to_string()), entry.senses[2].part_of_speech);
+        assert!(entry.mp3_link.unwrap().ends_with("present__gb_1.mp3"));
+
+        let merged = merge_definitions(&fixtures, &format!("{}_1", url), &format!("{}_5", url), &format!("{}_2", url))
+            .await
+            .unwrap();
+        assert_eq!(3, merged.definitions.len());
+    }
+
+    #[tokio::test]
+    async fn sense_page_failure_test() {
+        let dir = temp_dir("mk_scraper_sense_page_failure_tests");
+        let url = "https://www.oxfordlearnersdictionaries.com/definition/english/present";
+        let pages = [
+            sense_page(1, "adjective", &["being in a particular place"]),
+            sense_page(2, "noun", &["a thing that you give to somebody"]),
+        ];
+        // `present` redirects to `present_1`, which is not recorded: it must not be downloaded again.
+        std::fs::write(format!("{}/{}.html", dir, key(url)), &pages[0]).unwrap();
+        std::fs::write(format!("{}/{}_2.html", dir, key(url)), &pages[1]).unwrap();
+
+        // Page 3 is neither recorded nor missing, like a timeout on the live site.
+        let fixtures = FixtureFetcher::replay(&dir);
+        let partial = OxfordScraper.scrape(&fixtures, "present").await.unwrap();
+        assert_eq!(vec!["adjective", "noun"], partial.parts_of_speech());
+        assert_eq!(2, partial.senses.len());
+
+        std::fs::write(format!("{}/{}_3.notfound", dir, key(url)), url).unwrap();
+        let entry = OxfordScraper.scrape(&fixtures, "present").await.unwrap();
+        assert_eq!(vec!["adjective", "noun"], entry.parts_of_speech());
+    }
 }
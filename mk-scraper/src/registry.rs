@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use common_libs::error::ServiceExuctionResult;
+use lazy_static::lazy_static;
+use log::info;
+
+use crate::{
+    cambridge_scraper::CambridgeScraper,
+    collins_scraper::CollinsScraper,
+    dictionary_scraper::DictionaryScraper,
+    oxford_scraper::OxfordScraper,
+    scraper_config::{self, ConfiguredScraper},
+};
+
+lazy_static! {
+    static ref SCRAPERS: RwLock<Registry> = RwLock::new(Registry::default());
+}
+
+fn builtin() -> Vec<Arc<dyn DictionaryScraper>> {
+    vec![
+        Arc::new(CambridgeScraper),
+        Arc::new(CollinsScraper),
+        Arc::new(OxfordScraper),
+    ]
+}
+
+/// Scrapers by lowercase name. The process-wide one is behind the free
+/// functions below; tests build their own so they don't depend on each other.
+#[derive(Clone)]
+pub struct Registry {
+    scrapers: HashMap<String, Arc<dyn DictionaryScraper>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry {
+            scrapers: HashMap::new(),
+        };
+        for scraper in builtin() {
+            registry.register(scraper);
+        }
+        registry
+    }
+}
+
+impl Registry {
+    /// Adds a scraper, replacing any registered under the same name.
+    pub fn register(&mut self, scraper: Arc<dyn DictionaryScraper>) {
+        let name = scraper.name().to_lowercase();
+        info!("registering dictionary: {}", name);
+        self.scrapers.insert(name, scraper);
+    }
+
+    /// Registers a `ConfiguredScraper` for every entry in the file, replacing the
+    /// built-in scrapers of the same name. A missing file keeps the built-ins; an
+    /// invalid one is an error and nothing is registered.
+    pub fn load_config(&mut self, path: &str) -> ServiceExuctionResult<usize> {
+        if !Path::new(path).exists() {
+            info!("scrapers.config.not.found: {}. Using the built-in scrapers.", path);
+            return Ok(0);
+        }
+
+        let configs = scraper_config::load(path)?;
+        let count = configs.len();
+        for config in configs {
+            self.register(Arc::new(ConfiguredScraper::new(config)));
+        }
+        Ok(count)
+    }
+
+    pub fn lookup<S: AsRef<str>>(&self, name: S) -> Option<Arc<dyn DictionaryScraper>> {
+        let key = name.as_ref().trim().to_lowercase();
+        self.scrapers.get(&key).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.scrapers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Adds a scraper to the process-wide registry.
+pub fn register(scraper: Arc<dyn DictionaryScraper>) {
+    SCRAPERS.write().unwrap().register(scraper);
+}
+
+/// Loads `config/scrapers.yml` into the process-wide registry, see [`Registry::load_config`].
+pub fn load_config(path: &str) -> ServiceExuctionResult<usize> {
+    SCRAPERS.write().unwrap().load_config(path)
+}
+
+pub fn lookup<S: AsRef<str>>(name: S) -> Option<Arc<dyn DictionaryScraper>> {
+    SCRAPERS.read().unwrap().lookup(name)
+}
+
+pub fn contains<S: AsRef<str>>(name: S) -> bool {
+    lookup(name).is_some()
+}
+
+pub fn names() -> Vec<String> {
+    SCRAPERS.read().unwrap().names()
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use std::sync::Arc;
+
+    use crate::{dictionary_scraper::DictionaryScraper, model::Dictionary};
+
+    use super::{contains, lookup, names, Registry};
+
+    struct TestScraper;
+
+    impl DictionaryScraper for TestScraper {
+        fn name(&self) -> &str {
+            "test-dictionary"
+        }
+
+        fn dictionary(&self) -> Dictionary {
+            Dictionary::Registered(self.name().to_string())
+        }
+
+        fn base_url(&self) -> &str {
+            "https://dictionary.test/english/"
+        }
+
+        fn definition_query(&self) -> &str {
+            r#"span[class="def"]"#
+        }
+
+        fn mp3_query(&self) -> &str {
+            r#"audio"#
+        }
+
+        fn mp3_start_with(&self) -> &str {
+            "https://"
+        }
+    }
+
+    #[test]
+    fn builtin_test() {
+        assert!(names().starts_with(&[
+            "cambridge".to_string(),
+            "collins".to_string()
+        ]));
+        assert!(contains("Oxford"));
+        assert!(lookup("undefined").is_none());
+        assert_eq!("cambridge", lookup(" Cambridge ").unwrap().name());
+    }
+
+    #[test]
+    fn register_test() {
+        let mut registry = Registry::default();
+        assert!(registry.lookup("test-dictionary").is_none());
+
+        registry.register(Arc::new(TestScraper));
+        assert!(registry.names().contains(&"test-dictionary".to_string()));
+        let scraper = registry.lookup(" Test-Dictionary ").unwrap();
+        assert_eq!("https://dictionary.test/english/wind-up", scraper.url("wind up"));
+        assert_eq!(Dictionary::Registered("test-dictionary".to_string()), scraper.dictionary());
+        assert!(!contains("test-dictionary"));
+    }
+
+    #[test]
+    fn load_config_test() {
+        let mut registry = Registry::default();
+        assert_eq!(0, registry.load_config("../config/missing.yml").unwrap());
+        assert!(registry.load_config("Cargo.toml").is_err());
+        assert_eq!(3, registry.load_config("../config/scrapers.yml").unwrap());
+        assert!(registry.names().contains(&"oxford".to_string()));
+        assert!(registry.lookup("oxford").unwrap().numbered_senses());
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use common_libs::error::{ServiceErrorType, ServiceExuctionResult};
+use serde::{Deserialize, Serialize};
+
+use crate::model::DictionaryEntry;
+
+/// Words scraped at the same time by `scrape_all`.
+pub const MAX_CONCURRENT_SCRAPES: usize = 6;
+
+/// What `scrape_all_with` passes to its progress callback after every
+/// word/dictionary pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrapeProgress {
+    pub word: String,
+    pub dictionary: String,
+    pub found: bool,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Outcome of scraping a list of words, per dictionary name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrapeReport {
+    pub words: Vec<String>,
+    pub found: HashMap<String, Vec<DictionaryEntry>>,
+    /// Requested words with at least one entry; the headwords may differ.
+    pub matched: Vec<String>,
+    /// The dictionary answered that it has no such word.
+    pub not_found: HashMap<String, Vec<String>>,
+    /// Word and reason of everything else that went wrong.
+    pub errors: HashMap<String, Vec<(String, String)>>,
+}
+
+impl ScrapeReport {
+    pub fn new(words: Vec<String>) -> Self {
+        ScrapeReport {
+            words,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, dictionary: &str, word: &str, result: ServiceExuctionResult<DictionaryEntry>) {
+        let dictionary = dictionary.to_string();
+        match result {
+            Ok(entry) => {
+                if !self.matched.iter().any(|matched| matched == word) {
+                    self.matched.push(word.to_string());
+                }
+                self.found.entry(dictionary).or_default().push(entry)
+            }
+            Err(err) if err.error_type == ServiceErrorType::ResourceNotFound => {
+                self.not_found.entry(dictionary).or_default().push(word.to_string())
+            }
+            Err(err) => self
+                .errors
+                .entry(dictionary)
+                .or_default()
+                .push((word.to_string(), err.message)),
+        }
+    }
+
+    /// Words found in the dictionary, sorted.
+    pub fn found_in(&self, dictionary: &str) -> Vec<String> {
+        let mut words: Vec<String> = self
+            .found
+            .get(dictionary)
+            .map(|entries| entries.iter().map(|entry| entry.word.clone()).collect())
+            .unwrap_or_default();
+        words.sort();
+        words
+    }
+
+    /// Requested words found in at least one dictionary, in request order.
+    pub fn found_words(&self) -> Vec<String> {
+        self.words
+            .iter()
+            .filter(|word| self.matched.contains(word))
+            .cloned()
+            .collect()
+    }
+
+    /// Requested words no dictionary has, in request order.
+    pub fn missing_words(&self) -> Vec<String> {
+        self.words
+            .iter()
+            .filter(|word| !self.matched.contains(word))
+            .cloned()
+            .collect()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} words, {} found, {} missing; {} entries, {} not found and {} failed lookups",
+            self.words.len(),
+            self.found_words().len(),
+            self.missing_words().len(),
+            self.found.values().map(|entries| entries.len()).sum::<usize>(),
+            self.not_found.values().map(|words| words.len()).sum::<usize>(),
+            self.errors.values().map(|errors| errors.len()).sum::<usize>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod scrape_report_tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        fixtures::{FixtureFetcher, FIXTURES_DIR},
+        scrape_all_with,
+    };
+
+    use super::ScrapeProgress;
+
+    #[tokio::test]
+    async fn scrape_all_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        let words = vec!["correct".to_string(), "amisega".to_string(), "ab".to_string()];
+        let dictionaries = vec!["cambridge".to_string(), "oxford".to_string()];
+        let progress: Mutex<Vec<ScrapeProgress>> = Mutex::new(vec![]);
+
+        let report = scrape_all_with(&fixtures, words, &dictionaries, 2, &|step| {
+            progress.lock().unwrap().push(step.clone())
+        })
+        .await;
+
+        assert_eq!(vec!["correct"], report.found_words());
+        assert_eq!(vec!["amisega", "ab"], report.missing_words());
+        assert_eq!(vec!["correct"], report.found_in("cambridge"));
+        assert_eq!(1, report.found["oxford"].len());
+        assert_eq!(vec!["amisega"], report.not_found["cambridge"]);
+        assert_eq!(2, report.errors["oxford"].len());
+        assert!(!report.not_found.contains_key("oxford"));
+        assert!(report.errors["cambridge"].iter().any(|(word, _)| word == "ab"));
+
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(6, progress.len());
+        assert_eq!((1..=6).collect::<Vec<_>>(), progress.iter().map(|step| step.done).collect::<Vec<_>>());
+        assert!(progress.iter().all(|step| step.total == 6));
+        assert_eq!(2, progress.iter().filter(|step| step.found).count());
+        assert_eq!(
+            "3 words, 1 found, 2 missing; 2 entries, 1 not found and 3 failed lookups",
+            report.summary()
+        );
+    }
+}
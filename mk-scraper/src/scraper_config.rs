@@ -0,0 +1,304 @@
+use std::{collections::HashSet, fs};
+
+use common_libs::error::{ServiceError, ServiceErrorType, ServiceExuctionResult};
+use log::info;
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::NOT_FOUND,
+    dictionary_scraper::{DictionaryScraper, PhrasalVerbQueries, PronunciationQueries, SenseQueries},
+    model::Dictionary,
+    to_url,
+};
+
+pub const SCRAPERS_CONFIG: &str = "config/scrapers.yml";
+pub const WORD_PLACEHOLDER: &str = "{word}";
+
+/// One dictionary as described in `config/scrapers.yml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScraperConfig {
+    pub name: String,
+    /// Page URL with a `{word}` placeholder for the hyphenated word.
+    pub url: String,
+    pub definition: String,
+    pub mp3: String,
+    pub mp3_start_with: String,
+    /// Prepended to the pronunciation link when the site links it relatively.
+    #[serde(default)]
+    pub mp3_base_url: Option<String>,
+    #[serde(default)]
+    pub headwords: Vec<String>,
+    #[serde(default)]
+    pub senses: Option<SenseQueries>,
+    #[serde(default)]
+    pub pronunciations: PronunciationQueries,
+    #[serde(default)]
+    pub phrasal_verbs: Option<PhrasalVerbQueries>,
+    #[serde(default = "default_not_found")]
+    pub not_found: Vec<String>,
+    #[serde(default)]
+    pub browser_fallback: bool,
+    #[serde(default)]
+    pub keep_incomplete: bool,
+    #[serde(default)]
+    pub numbered_senses: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapersConfig {
+    pub scrapers: Vec<ScraperConfig>,
+}
+
+fn default_not_found() -> Vec<String> {
+    NOT_FOUND.iter().map(|marker| marker.to_string()).collect()
+}
+
+impl ScraperConfig {
+    pub fn validate(&self) -> ServiceExuctionResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(invalid("scraper.name.is.empty"));
+        }
+
+        if !self.url.starts_with("http") || !self.url.contains(WORD_PLACEHOLDER) {
+            return Err(invalid(&format!(
+                "invalid.url: {} -> {} (expected http(s) with {})",
+                self.name, self.url, WORD_PLACEHOLDER
+            )));
+        }
+
+        if self.mp3_start_with.is_empty() {
+            return Err(invalid(&format!("mp3_start_with.is.empty: {}", self.name)));
+        }
+
+        if self.not_found.iter().any(|marker| marker.trim().is_empty()) {
+            return Err(invalid(&format!("empty.not_found.marker: {}", self.name)));
+        }
+
+        let mut selectors = vec![self.definition.as_str(), self.mp3.as_str()];
+        selectors.extend(self.headwords.iter().map(|selector| selector.as_str()));
+        if let Some(senses) = &self.senses {
+            selectors.extend(senses.selectors());
+        }
+        selectors.extend(self.pronunciations.selectors());
+        if let Some(phrasal_verbs) = &self.phrasal_verbs {
+            selectors.extend(phrasal_verbs.selectors());
+        }
+        for selector in selectors {
+            if Selector::parse(selector).is_err() {
+                return Err(invalid(&format!("invalid.selector: {} -> {}", self.name, selector)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> ServiceError {
+    ServiceError {
+        message: message.to_string(),
+        error_type: ServiceErrorType::Failure,
+    }
+}
+
+/// Reads and validates every scraper in the file; a single broken entry fails the whole file.
+pub fn load(path: &str) -> ServiceExuctionResult<Vec<ScraperConfig>> {
+    let content = fs::read_to_string(path)?;
+    let config: ScrapersConfig = serde_yaml::from_str(&content)
+        .map_err(|err| invalid(&format!("invalid.scrapers.config: {} -> {}", path, err)))?;
+
+    let mut names = HashSet::new();
+    for scraper in &config.scrapers {
+        scraper.validate()?;
+        if !names.insert(scraper.name.to_lowercase()) {
+            return Err(invalid(&format!("duplicated.scraper: {}", scraper.name)));
+        }
+    }
+    info!("{} scrapers loaded from {}", config.scrapers.len(), path);
+    Ok(config.scrapers)
+}
+
+/// A scraper driven entirely by its `ScraperConfig`.
+#[derive(Debug, Clone)]
+pub struct ConfiguredScraper {
+    config: ScraperConfig,
+    name: String,
+    base_url: String,
+    not_found: Vec<String>,
+    dictionary: Dictionary,
+}
+
+impl ConfiguredScraper {
+    pub fn new(config: ScraperConfig) -> Self {
+        let name = config.name.trim().to_lowercase();
+        let base_url = config.url.split(WORD_PLACEHOLDER).next().unwrap_or_default().to_string();
+        let not_found = config.not_found.iter().map(|marker| marker.to_lowercase()).collect();
+        let dictionary = match Dictionary::from(name.clone()) {
+            Dictionary::Undefined => Dictionary::Registered(name.clone()),
+            dictionary => dictionary,
+        };
+        ConfiguredScraper {
+            config,
+            name,
+            base_url,
+            not_found,
+            dictionary,
+        }
+    }
+
+    pub fn config(&self) -> &ScraperConfig {
+        &self.config
+    }
+}
+
+impl DictionaryScraper for ConfiguredScraper {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn dictionary(&self) -> Dictionary {
+        self.dictionary.clone()
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn definition_query(&self) -> &str {
+        &self.config.definition
+    }
+
+    fn mp3_query(&self) -> &str {
+        &self.config.mp3
+    }
+
+    fn mp3_start_with(&self) -> &str {
+        &self.config.mp3_start_with
+    }
+
+    fn headword_queries(&self) -> Vec<&str> {
+        self.config.headwords.iter().map(|query| query.as_str()).collect()
+    }
+
+    fn browser_fallback(&self) -> bool {
+        self.config.browser_fallback
+    }
+
+    fn keep_incomplete(&self) -> bool {
+        self.config.keep_incomplete
+    }
+
+    fn numbered_senses(&self) -> bool {
+        self.config.numbered_senses
+    }
+
+    fn not_found_markers(&self) -> Vec<&str> {
+        self.not_found.iter().map(|marker| marker.as_str()).collect()
+    }
+
+    fn mp3_base_url(&self) -> Option<&str> {
+        self.config.mp3_base_url.as_deref()
+    }
+
+    fn sense_queries(&self) -> Option<SenseQueries> {
+        self.config.senses.clone()
+    }
+
+    fn pronunciation_queries(&self) -> PronunciationQueries {
+        self.config.pronunciations.clone()
+    }
+
+    fn phrasal_verb_queries(&self) -> Option<PhrasalVerbQueries> {
+        self.config.phrasal_verbs.clone()
+    }
+
+    fn url(&self, word: &str) -> String {
+        let suffix = self.config.url.split(WORD_PLACEHOLDER).nth(1).unwrap_or_default();
+        [to_url(&self.base_url, word), suffix.to_string()].join("")
+    }
+}
+
+#[cfg(test)]
+mod scraper_config_tests {
+    use crate::{
+        cambridge_scraper::CambridgeScraper,
+        collins_scraper::CollinsScraper,
+        dictionary_scraper::DictionaryScraper,
+        fixtures::{FixtureFetcher, FIXTURES_DIR},
+        oxford_scraper::OxfordScraper,
+    };
+
+    use super::{load, ConfiguredScraper, ScraperConfig};
+
+    const CONFIG: &str = "../config/scrapers.yml";
+
+    fn configured(name: &str) -> ConfiguredScraper {
+        let config = load(CONFIG)
+            .unwrap()
+            .into_iter()
+            .find(|config| config.name == name)
+            .unwrap();
+        ConfiguredScraper::new(config)
+    }
+
+    #[test]
+    fn load_test() {
+        let configs = load(CONFIG).unwrap();
+        assert_eq!(3, configs.len());
+
+        let oxford = configured("oxford");
+        assert_eq!(
+            "https://www.oxfordlearnersdictionaries.com/definition/english/wind-up",
+            oxford.url("wind up")
+        );
+        assert_eq!(OxfordScraper.url("wind up"), oxford.url("wind up"));
+        assert!(oxford.numbered_senses());
+        assert_eq!(4, oxford.not_found_markers().len());
+    }
+
+    #[test]
+    fn validate_test() {
+        let valid = configured("collins").config().clone();
+        assert!(valid.validate().is_ok());
+
+        let no_placeholder = ScraperConfig {
+            url: "https://www.collinsdictionary.com/dictionary/english/".to_string(),
+            ..valid.clone()
+        };
+        assert!(no_placeholder.validate().is_err());
+
+        let broken_selector = ScraperConfig {
+            definition: r#"div[class="sense"]]"#.to_string(),
+            ..valid.clone()
+        };
+        assert!(broken_selector.validate().is_err());
+
+        let broken_headword = ScraperConfig {
+            headwords: vec!["h1..headword".to_string()],
+            ..valid
+        };
+        assert!(broken_headword.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn configured_replay_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+
+        let cambridge = configured("cambridge").scrape(&fixtures, "correct").await.unwrap();
+        let expected = CambridgeScraper.scrape(&fixtures, "correct").await.unwrap();
+        assert_eq!(expected.mp3_link, cambridge.mp3_link);
+        assert_eq!(expected.definitions, cambridge.definitions);
+        assert_eq!(expected.senses, cambridge.senses);
+        assert_eq!(expected.uk, cambridge.uk);
+
+        let collins = configured("collins").scrape(&fixtures, "rampage").await.unwrap();
+        let expected = CollinsScraper.scrape(&fixtures, "rampage").await.unwrap();
+        assert_eq!(expected.definitions, collins.definitions);
+        assert_eq!(expected.senses, collins.senses);
+        assert_eq!(expected.us, collins.us);
+
+        let oxford = configured("oxford").scrape(&fixtures, "correct").await.unwrap();
+        let expected = OxfordScraper.scrape(&fixtures, "correct").await.unwrap();
+        assert_eq!(expected.definitions.len(), oxford.definitions.len());
+        assert_eq!(expected.senses, oxford.senses);
+    }
+}
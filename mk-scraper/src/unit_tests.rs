@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod runit {
+    use std::path::Path;
+
     use common_libs::files::{file_name, read_file_content};
     use itertools::Itertools;
 
-    use crate::{to_url, download_from_url, mp3_element, first_element, description, elements, constants::{PAGE_NOT_FOUND, NOT_FOUND, HTML_EXT}, merge_definitions, oxford_scraper::check_for_more};
+    use crate::{to_url, download_from_url, download_as_browser, mp3_element, first_element, description, elements, constants::{PAGE_NOT_FOUND, NOT_FOUND, HTML_EXT}, merge_definitions, oxford_scraper::check_for_more, http_client, fixtures::{FixtureFetcher, FIXTURES_DIR}, download_with};
 
 
     const CAMBRIDGE_URL: &str = "https://dictionary.cambridge.org/dictionary/english/";
@@ -34,6 +36,7 @@ mod runit {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn scrape_words_success_test() {
         let words = vec!["dust up", "cling", "stark", "villain", "bail out"];
         for w in words {
@@ -46,7 +49,7 @@ mod runit {
 
     #[tokio::test]
     async fn mp3_url_test() {
-        let url = to_url(OXFORD_URL, "bail out");
+        let url = to_url(OXFORD_URL, "correct");
         let found = download_from_url(&url).await;
         assert!(found.is_ok());
         let html = found.unwrap();
@@ -58,7 +61,7 @@ mod runit {
         println!("mp3 element: {}", mp3);
         assert!(!mp3.is_empty());
 
-        let url = to_url(CAMBRIDGE_URL, "bail out");
+        let url = to_url(CAMBRIDGE_URL, "correct");
         let found = download_from_url(&url).await;
         assert!(found.is_ok());
         let html = found.unwrap();
@@ -68,6 +71,7 @@ mod runit {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn definition_test() {
         let url = to_url(CAMBRIDGE_URL, "bail out");
         let html = download_from_url(&url).await.unwrap();
@@ -106,56 +110,22 @@ mod runit {
 
     #[tokio::test]
     async fn test_collins_url() {
-        let cmd = format!("wget --user-agent=\"Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.1 Safari/605.1.15\" https://www.collinsdictionary.com/dictionary/english/{} -O download/tmp/collins/{}.html", "correct", "correct");
-        println!("cmd: {}", cmd);
-        let command = std::process::Command::new("pwd").output().unwrap();
-        println!("output: {:?}", String::from_utf8(command.stdout).unwrap());
-
-        std::process::Command::new("mkdir")
-            .arg("-p")
-            .arg("download/tmp/collins")
-            .status()
-            .expect("failed to execute mkdir");
-        std::process::Command::new("mkdir")
-            .arg("-p")
-            .arg("download/tmp/cambridge")
-            .status()
-            .expect("failed to execute mkdir");
-        std::process::Command::new("mkdir")
-            .arg("-p")
-            .arg("download/tmp/oxford")
-            .status()
-            .expect("failed to execute mkdir");
-
-        std::process::Command::new("wget")
-            .arg("--user-agent")
-            .arg("'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.1 Safari/605.1.15'")
-            .arg("https://www.collinsdictionary.com/dictionary/english/rampage")
-            .arg("-O")
-            .arg("download/tmp/collins/rampage.html")
-            .status()
-            .expect("failed to execute wget");
-
-        std::process::Command::new("wget")
-            .arg("--user-agent")
-            .arg("'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.1 Safari/605.1.15'")
-            .arg("https://dictionary.cambridge.org/dictionary/english/correct")
-            .arg("-O")
-            .arg("download/tmp/cambridge/correct.html")
-            .status()
-            .expect("failed to execute wget");
-
-        std::process::Command::new("wget")
-            .arg("--user-agent")
-            .arg("'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.1 Safari/605.1.15'")
-            .arg("https://www.oxfordlearnersdictionaries.com/definition/english/correct")
-            .arg("-O")
-            .arg("download/tmp/oxford/correct.html")
-            .status()
-            .expect("failed to execute wget");
+        let tmp = std::env::temp_dir().join("mk_scraper_unit_tests");
+        let pages = vec![
+            ("https://www.collinsdictionary.com/dictionary/english/rampage", "collins/rampage.html"),
+            ("https://dictionary.cambridge.org/dictionary/english/correct", "cambridge/correct.html"),
+            ("https://www.oxfordlearnersdictionaries.com/definition/english/correct", "oxford/correct.html"),
+        ];
+        for (url, file_name) in pages {
+            let file_name = tmp.join(file_name).to_string_lossy().to_string();
+            let html = download_as_browser(http_client::default_fetcher(), url, &file_name).await;
+            assert!(html.is_ok());
+            assert!(Path::new(&file_name).exists());
+        }
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn oxford_redirect_test() {
         let word = "unrelenting";
         let url = to_url(OXFORD_URL, word);
@@ -184,6 +154,7 @@ mod runit {
     }
 
     #[tokio::test]
+    #[ignore = "live: run with MK_SCRAPER_FIXTURES=record to record its pages"]
     async fn oxford_first_element_test() {
         let url = to_url(OXFORD_URL, "unrelenting");
         let html = download_from_url(&url).await.unwrap();
@@ -195,16 +166,16 @@ mod runit {
         ))
     }
 
-    #[test]
-    fn download_sth() {
-        std::process::Command::new("wget")
-        .arg("--user-agent")
-        .arg("'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.1 Safari/605.1.15'")
-        .arg("https://www.oxfordlearnersdictionaries.com/definition/english/correct_2")
-        .arg("-O")
-        .arg("download/tmp/oxford/correct_2.html")
-        .status()
-        .expect("failed to execute wget");
+    #[tokio::test]
+    async fn download_sth() {
+        let file_name = std::env::temp_dir().join("mk_scraper_unit_tests/oxford/correct_2.html");
+        let html = download_as_browser(
+            http_client::default_fetcher(),
+            "https://www.oxfordlearnersdictionaries.com/definition/english/correct_2",
+            &file_name.to_string_lossy(),
+        )
+        .await;
+        assert!(html.is_ok());
     }
 
     #[test]
@@ -229,6 +200,7 @@ mod runit {
     #[tokio::test]
     async fn merge_correct2_test() {
         let res = merge_definitions(
+            http_client::default_fetcher(),
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_1"#,
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#,
             r#""#,
@@ -241,6 +213,7 @@ mod runit {
     #[tokio::test]
     async fn merge_correct3_test() {
         let res = merge_definitions(
+            http_client::default_fetcher(),
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_1"#,
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#,
             r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_3"#,
@@ -249,4 +222,28 @@ mod runit {
         assert!(res.is_some());
         println!("found: {:?}", res.unwrap());
     }
+
+    #[tokio::test]
+    async fn replay_selectors_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+
+        let html = download_with(&fixtures, &to_url(CAMBRIDGE_URL, "correct")).await.unwrap();
+        let definitions = elements(r#"div[class="def ddef_d db"]"#, &html, true);
+        assert_eq!(9, definitions.len());
+        assert!(mp3_element(r#"source[type="audio/mpeg"]"#, &html).is_some());
+
+        let html = download_with(&fixtures, &to_url(OXFORD_URL, "correct")).await.unwrap();
+        assert!(mp3_element(
+            r#"div[class="sound audio_play_button pron-uk icon-audio"]"#,
+            &html,
+        )
+        .is_some());
+        assert_eq!(
+            Some(String::from(
+                r#"https://www.oxfordlearnersdictionaries.com/definition/english/correct_2"#
+            )),
+            check_for_more("correct", &html)
+        );
+        assert!(download_with(&fixtures, &to_url(OXFORD_URL, "correct_3")).await.is_err());
+    }
 }
@@ -0,0 +1,335 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use common_libs::error::ServiceExuctionResult;
+use log::{error, info};
+use serde::Deserialize;
+
+use crate::{
+    dictionary_scraper::{not_found, validate_word, DictionaryScraper},
+    http_client::Fetcher,
+    model::{Dictionary, DictionaryEntry, Pronunciation, Sense, SCHEMA_VERSION},
+    registry,
+};
+
+/// A kaikki.org style extract: one JSON object per word and part of speech.
+pub const WIKTIONARY_DUMP: &str = "data/wiktionary/kaikki.jsonl";
+pub const WIKTIONARY_NAME: &str = "wiktionary";
+pub const WIKTIONARY_URL: &str = "https://en.wiktionary.org/wiki/";
+const ENGLISH: &str = "en";
+const GRAMMAR_TAGS: [&str; 4] = ["transitive", "intransitive", "countable", "uncountable"];
+
+#[derive(Debug, Clone, Deserialize)]
+struct KaikkiRecord {
+    word: String,
+    #[serde(default)]
+    lang_code: Option<String>,
+    #[serde(default)]
+    pos: Option<String>,
+    #[serde(default)]
+    senses: Vec<KaikkiSense>,
+    #[serde(default)]
+    sounds: Vec<KaikkiSound>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KaikkiSense {
+    /// From the general to the specific meaning; the last one is the definition.
+    #[serde(default)]
+    glosses: Vec<String>,
+    #[serde(default)]
+    examples: Vec<KaikkiExample>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KaikkiExample {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KaikkiSound {
+    #[serde(default)]
+    ipa: Option<String>,
+    #[serde(default)]
+    mp3_url: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl KaikkiSound {
+    fn is_uk(&self) -> bool {
+        self.tags.iter().any(|tag| ["UK", "Received-Pronunciation"].contains(&tag.as_str()))
+    }
+
+    fn is_us(&self) -> bool {
+        self.tags.iter().any(|tag| ["US", "General-American"].contains(&tag.as_str()))
+    }
+}
+
+/// English entries of a Wiktionary extract, indexed by the lower case word.
+/// Records of the same word (one per part of speech) are merged into one entry.
+#[derive(Debug, Clone, Default)]
+pub struct Wiktionary {
+    entries: HashMap<String, DictionaryEntry>,
+}
+
+impl Wiktionary {
+    /// Reads the whole file; lines that are not valid records are logged and skipped.
+    pub fn load(path: &str) -> ServiceExuctionResult<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut wiktionary = Wiktionary::default();
+        let mut skipped = 0;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<KaikkiRecord>(&line) {
+                Ok(record) => wiktionary.add(record),
+                Err(err) => {
+                    skipped += 1;
+                    error!("invalid.wiktionary.record: {}:{} -> {}", path, number + 1, err);
+                }
+            }
+        }
+        info!("{} words loaded from {}, {} lines skipped", wiktionary.len(), path, skipped);
+        Ok(wiktionary)
+    }
+
+    fn add(&mut self, record: KaikkiRecord) {
+        if record.lang_code.as_deref().unwrap_or(ENGLISH) != ENGLISH {
+            return;
+        }
+        let entry = match to_entry(record) {
+            Some(entry) => entry,
+            None => return,
+        };
+        match self.entries.get_mut(&key(&entry.word)) {
+            Some(known) => known.append(entry),
+            None => {
+                self.entries.insert(key(&entry.word), entry);
+            }
+        }
+    }
+
+    pub fn lookup(&self, word: &str) -> Option<&DictionaryEntry> {
+        self.entries.get(&key(word))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = self.entries.values().map(|entry| entry.word.clone()).collect();
+        words.sort();
+        words
+    }
+}
+
+/// Loads the extract and registers it as the `wiktionary` dictionary. Like
+/// `registry::load_config`, a missing file is not an error.
+pub fn register(path: &str) -> ServiceExuctionResult<usize> {
+    if !Path::new(path).exists() {
+        info!("wiktionary.dump.not.found: {}", path);
+        return Ok(0);
+    }
+    let wiktionary = Wiktionary::load(path)?;
+    let count = wiktionary.len();
+    registry::register(Arc::new(wiktionary));
+    Ok(count)
+}
+
+fn key(word: &str) -> String {
+    word.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn url(word: &str) -> String {
+    [WIKTIONARY_URL, &word.split_whitespace().collect::<Vec<_>>().join("_")].join("")
+}
+
+fn to_entry(record: KaikkiRecord) -> Option<DictionaryEntry> {
+    let part_of_speech = record.pos.clone();
+    let senses: Vec<Sense> = record
+        .senses
+        .iter()
+        .filter_map(|sense| to_sense(sense, part_of_speech.clone()))
+        .collect();
+    if senses.is_empty() {
+        return None;
+    }
+
+    let uk = pronunciation(record.sounds.iter().filter(|sound| sound.is_uk()));
+    let us = pronunciation(record.sounds.iter().filter(|sound| sound.is_us()));
+    let word = record.word.trim().to_string();
+    Some(DictionaryEntry {
+        schema_version: SCHEMA_VERSION,
+        source: Dictionary::Wiktionary,
+        url: url(&word),
+        mp3_link: uk.as_ref().or(us.as_ref()).and_then(|pronunciation| pronunciation.mp3_link.clone()),
+        definitions: senses.iter().map(|sense| sense.definition.clone()).collect(),
+        word,
+        file: None,
+        senses,
+        uk,
+        us,
+    })
+}
+
+fn to_sense(sense: &KaikkiSense, part_of_speech: Option<String>) -> Option<Sense> {
+    let definition = sense.glosses.last()?.trim().to_string();
+    if definition.is_empty() {
+        return None;
+    }
+    let grammar = sense
+        .tags
+        .iter()
+        .find(|tag| GRAMMAR_TAGS.contains(&tag.as_str()))
+        .map(|tag| ["[", tag, "]"].join(""));
+    let region = sense.tags.iter().find_map(|tag| match tag.as_str() {
+        "UK" | "British" => Some("British".to_string()),
+        "US" | "American" => Some("American".to_string()),
+        _ => None,
+    });
+    Some(Sense {
+        definition,
+        part_of_speech,
+        grammar,
+        examples: sense
+            .examples
+            .iter()
+            .filter_map(|example| example.text.as_ref())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect(),
+        cefr: None,
+        domains: sense.topics.clone(),
+        region,
+        sources: vec![],
+    })
+}
+
+/// The first sound with an IPA and the first with an mp3 of one accent.
+fn pronunciation<'a>(mut sounds: impl Iterator<Item = &'a KaikkiSound> + Clone) -> Option<Pronunciation> {
+    let ipa = sounds.clone().find_map(|sound| sound.ipa.clone());
+    let mp3_link = sounds.find_map(|sound| sound.mp3_url.clone());
+    if ipa.is_none() && mp3_link.is_none() {
+        return None;
+    }
+    Some(Pronunciation { ipa, mp3_link })
+}
+
+/// Served from memory: nothing is downloaded and the fetcher is never used.
+#[async_trait]
+impl DictionaryScraper for Wiktionary {
+    fn name(&self) -> &str {
+        WIKTIONARY_NAME
+    }
+
+    fn dictionary(&self) -> Dictionary {
+        Dictionary::Wiktionary
+    }
+
+    fn base_url(&self) -> &str {
+        WIKTIONARY_URL
+    }
+
+    fn definition_query(&self) -> &str {
+        ""
+    }
+
+    fn mp3_query(&self) -> &str {
+        ""
+    }
+
+    fn mp3_start_with(&self) -> &str {
+        "https://"
+    }
+
+    fn url(&self, word: &str) -> String {
+        url(word)
+    }
+
+    async fn scrape(&self, _fetcher: &dyn Fetcher, word: &str) -> ServiceExuctionResult<DictionaryEntry> {
+        validate_word(word)?;
+        self.lookup(word)
+            .cloned()
+            .ok_or_else(|| not_found(&format!("not.found.in.wiktionary: {}", word)))
+    }
+}
+
+#[cfg(test)]
+mod wiktionary_tests {
+    use common_libs::error::ServiceErrorType;
+
+    use crate::{
+        fixtures::{FixtureFetcher, FIXTURES_DIR},
+        model::Dictionary,
+        registry, scrape_it_with,
+    };
+
+    use super::{register, Wiktionary};
+
+    const DUMP: &str = "fixtures/kaikki.jsonl";
+
+    #[test]
+    fn load_test() {
+        let wiktionary = Wiktionary::load(DUMP).unwrap();
+        assert_eq!(vec!["correct", "rampage", "wind up"], wiktionary.words());
+        assert!(Wiktionary::load("fixtures/missing.jsonl").is_err());
+
+        let correct = wiktionary.lookup(" Correct ").unwrap();
+        assert!(matches!(correct.source, Dictionary::Wiktionary));
+        assert_eq!("https://en.wiktionary.org/wiki/correct", correct.url);
+        assert_eq!(vec!["adj", "verb"], correct.parts_of_speech());
+        assert_eq!(4, correct.senses.len());
+        assert_eq!(correct.definitions.len(), correct.senses.len());
+        assert_eq!("Free from error; true; accurate.", correct.definitions[0]);
+        assert_eq!(Some("[transitive]".to_string()), correct.senses[2].grammar);
+        assert_eq!(vec!["mathematics"], correct.senses[1].domains);
+        assert_eq!(
+            vec!["The teacher corrected the students' homework."],
+            correct.senses[2].examples
+        );
+        assert_eq!(Some("/kəˈɹɛkt/".to_string()), correct.uk.as_ref().unwrap().ipa);
+        assert_eq!(correct.mp3_link, correct.uk.as_ref().unwrap().mp3_link);
+        assert!(correct.us.as_ref().unwrap().mp3_link.is_none());
+
+        let wind_up = wiktionary.lookup("wind  up").unwrap();
+        assert_eq!("https://en.wiktionary.org/wiki/wind_up", wind_up.url);
+        assert_eq!(Some("British".to_string()), wind_up.senses[1].region);
+        assert!(wind_up.mp3_link.is_none());
+    }
+
+    #[tokio::test]
+    async fn scrape_test() {
+        let fixtures = FixtureFetcher::replay(FIXTURES_DIR);
+        assert_eq!(3, register(DUMP).unwrap());
+        assert_eq!(0, register("fixtures/missing.jsonl").unwrap());
+        assert!(matches!(Dictionary::from("Wiktionary".to_string()), Dictionary::Wiktionary));
+
+        let wiktionary = registry::lookup("wiktionary").unwrap();
+        let rampage = wiktionary.scrape(&fixtures, "rampage").await.unwrap();
+        assert_eq!(vec!["noun", "verb"], rampage.parts_of_speech());
+        let err = wiktionary.scrape(&fixtures, "amisega").await.unwrap_err();
+        assert_eq!(ServiceErrorType::ResourceNotFound, err.error_type);
+
+        let wind_up = scrape_it_with(&fixtures, "wiktionary".to_string(), "wind up").await.unwrap();
+        assert_eq!(2, wind_up.senses.len());
+    }
+}
@@ -0,0 +1,418 @@
+use std::time::Duration;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+use common_libs::{scheduler::PASSING_SCORE, scoring::Algorithm};
+use mk_scraper::{
+    audio::{JSON_DIR, MP3_DIR},
+    cache::{CacheConfig, CACHE_TTL_SECS},
+    dictionary_scraper::TMP_DIR,
+    http_client::{HttpClientConfig, CONNECT_TIMEOUT_SECS, POOL_MAX_IDLE_PER_HOST, REQUEST_TIMEOUT_SECS},
+    migration::DATA_DIR,
+    registry,
+};
+
+/// `download --dictionary all` scrapes every `MERGED_DICTIONARIES` one.
+pub const ALL_DICTIONARIES: &str = "all";
+pub const CACHE_TTL_HOURS: u64 = CACHE_TTL_SECS / 3600;
+
+/// Derive-based equivalent of `config/config.yml`.
+#[derive(Debug, Clone, Parser)]
+#[command(name = "matkat", version = "1.0", author = "Alex T. <alexander.todorov@ayagasha.com>")]
+#[command(about = "Self Teacher")]
+pub struct Cli {
+    /// Configure the download json folder [default: data/en-en/<dictionary>/json].
+    #[arg(short, long, value_name = "DATA")]
+    pub json_dir: Option<String>,
+
+    /// Configure the download mp3 folder [default: data/en-en/<dictionary>/mp3].
+    #[arg(short, long, value_name = "MP3")]
+    pub mp3_dir: Option<String>,
+
+    /// Which dictionary should be used.
+    #[arg(short, long, value_name = "DICTIONARY", default_value = "cambridge", value_parser = dictionary)]
+    pub dictionary: String,
+
+    /// File path to the word list.
+    #[arg(short, long, value_name = "FILE", default_value = "")]
+    pub file: String,
+
+    /// How many times each word will be played/pronounced.
+    #[arg(short, long, value_name = "ITERATIONS", default_value_t = 10)]
+    pub iterations: u32,
+
+    /// 100 means full match. Less than 100 means how close you are.
+    #[arg(short = 's', long, value_name = "SCORE", default_value_t = PASSING_SCORE,
+        value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub passing_score: u8,
+
+    /// How answers are compared: levenshtein, damerau, jaro-winkler or token-set.
+    #[arg(short, long, value_name = "ALGORITHM", default_value = "damerau")]
+    pub algorithm: Algorithm,
+
+    /// How long the program should wait for your response (in seconds).
+    #[arg(short, long, value_name = "TIMEOUT", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// The maximum number of questions (word entries).
+    #[arg(short = 'q', long = "maximum-questions", value_name = "QUESTIONS", default_value_t = 20)]
+    pub maximum_questions: usize,
+
+    /// Idle connections kept open per dictionary website.
+    #[arg(long, value_name = "CONNECTIONS", default_value_t = POOL_MAX_IDLE_PER_HOST)]
+    pub pool_size: usize,
+
+    /// How long to wait for a dictionary website to accept the connection (in seconds).
+    #[arg(long, value_name = "SECONDS", default_value_t = CONNECT_TIMEOUT_SECS)]
+    pub connect_timeout: u64,
+
+    /// How long a whole download may take (in seconds).
+    #[arg(long, value_name = "SECONDS", default_value_t = REQUEST_TIMEOUT_SECS)]
+    pub request_timeout: u64,
+
+    /// Send the downloads through a proxy, e.g. http://127.0.0.1:3128.
+    #[arg(long, value_name = "PROXY")]
+    pub proxy: Option<String>,
+
+    /// How long a downloaded page is reused before asking the website again (in hours).
+    #[arg(long, value_name = "HOURS", default_value_t = CACHE_TTL_HOURS)]
+    pub cache_ttl: u64,
+
+    /// Download the pages again even if they are cached.
+    #[arg(long)]
+    pub refresh: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Download all words from a list or from the terminal.
+    Download(DownloadArgs),
+    /// Listen the pronunciation.
+    Listen(ListenArgs),
+    /// Test how good you are =:).
+    Quiz(QuizArgs),
+    /// Show the progress over all sessions.
+    Stats,
+    /// Export the downloaded words as a flashcard deck.
+    Export(ExportArgs),
+    /// Turn a text from --file into a list of new words to download.
+    Ingest(IngestArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum InputMode {
+    File,
+    Manual,
+}
+
+/// Which side of `data/translations.csv` the quiz shows.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Direction {
+    /// Show the Bulgarian, answer in English.
+    BgEn,
+    /// Show the English, answer in Bulgarian.
+    EnBg,
+}
+
+impl Direction {
+    /// The word's key in the statistic, e.g. `bg-en:call off`; each
+    /// direction is scheduled apart from the other and from the word itself.
+    pub fn statistic_key(&self, word: &str) -> String {
+        let direction = match self {
+            Direction::BgEn => "bg-en",
+            Direction::EnBg => "en-bg",
+        };
+        format!("{}:{}", direction, word.trim().to_lowercase())
+    }
+}
+
+/// `-h` is taken by `--tmp`, so help is `--help` only.
+#[derive(Debug, Clone, Args)]
+#[command(disable_help_flag = true)]
+pub struct DownloadArgs {
+    /// Where the words come from.
+    #[arg(short = 'i', long = "input", value_name = "INPUT", value_enum, default_value = "manual")]
+    pub mode: InputMode,
+
+    /// Where html pages should be downloaded.
+    #[arg(short = 'h', long = "tmp", value_name = "TMP", default_value = TMP_DIR)]
+    pub html_tmp_folder: String,
+
+    /// Which dictionary should be used, or `all`; overrides the global one.
+    #[arg(short, long, value_name = "DICTIONARY", value_parser = download_dictionary)]
+    pub dictionary: Option<String>,
+
+    #[arg(long, action = ArgAction::Help)]
+    help: Option<bool>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ListenArgs {
+    /// Score the input using any of the definitions.
+    #[arg(short, long, value_name = "DEFINITION", default_value_t = false, action = ArgAction::Set)]
+    pub use_definition: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Anki package with the audio.
+    Apkg,
+    Csv,
+    Tsv,
+}
+
+impl ExportFormat {
+    pub fn ext(&self) -> &str {
+        match self {
+            ExportFormat::Apkg => ".apkg",
+            ExportFormat::Csv => ".csv",
+            ExportFormat::Tsv => ".tsv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportArgs {
+    /// The deck format.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value = "apkg")]
+    pub format: ExportFormat,
+
+    /// Where the deck is saved [default: data/export/<dictionary>.<format>].
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<String>,
+
+    /// The name of the Anki deck [default: matkat::<dictionary>].
+    #[arg(long, value_name = "DECK")]
+    pub deck: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct IngestArgs {
+    /// Where the word list is saved; printed when missing.
+    #[arg(short, long, value_name = "OUTPUT")]
+    pub output: Option<String>,
+
+    /// File with more words you already know, one per line.
+    #[arg(short, long, value_name = "KNOWN")]
+    pub known: Option<String>,
+}
+
+/// `-h` is taken by `--hide`, so help is `--help` only.
+#[derive(Debug, Clone, Args)]
+#[command(disable_help_flag = true)]
+pub struct QuizArgs {
+    /// Score the input using any of the definitions.
+    #[arg(short, long, value_name = "DEFINITION", default_value_t = false, action = ArgAction::Set)]
+    pub use_definition: bool,
+
+    /// Hide the word if it is found in definition.
+    #[arg(short = 'h', long = "hide", value_name = "HIDE", default_value_t = false, action = ArgAction::Set)]
+    pub hide_word: bool,
+
+    /// Translate the words of the glossary instead of defining them.
+    #[arg(short = 'r', long = "translate", value_name = "DIRECTION", value_enum)]
+    pub translate: Option<Direction>,
+
+    #[arg(long, action = ArgAction::Help)]
+    help: Option<bool>,
+}
+
+/// Any registered dictionary, including the ones from `config/scrapers.yml`.
+fn dictionary(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if registry::contains(&name) {
+        Ok(name)
+    } else {
+        Err(format!("expected one of: {}", registry::names().join(", ")))
+    }
+}
+
+fn download_dictionary(name: &str) -> Result<String, String> {
+    match name.trim().to_lowercase().as_str() {
+        ALL_DICTIONARIES => Ok(ALL_DICTIONARIES.to_string()),
+        _ => dictionary(name),
+    }
+}
+
+impl Cli {
+    pub fn dictionary(&self) -> String {
+        match &self.command {
+            Command::Download(DownloadArgs {
+                dictionary: Some(dictionary),
+                ..
+            }) => dictionary.clone(),
+            _ => self.dictionary.clone(),
+        }
+    }
+
+    pub fn json_dir(&self) -> String {
+        self.json_dir_for(&self.dictionary())
+    }
+
+    /// `--json-dir` as it is for a single dictionary; with `all` it holds a
+    /// folder per dictionary.
+    pub fn json_dir_for(&self, dictionary: &str) -> String {
+        match &self.json_dir {
+            Some(json_dir) if self.dictionary() == ALL_DICTIONARIES => [json_dir.as_str(), dictionary].join("/"),
+            Some(json_dir) => json_dir.clone(),
+            None => [DATA_DIR, dictionary, JSON_DIR].join("/"),
+        }
+    }
+
+    /// The shared HTTP client as configured on the command line.
+    pub fn http_client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            pool_max_idle_per_host: self.pool_size,
+            connect_timeout: Duration::from_secs(self.connect_timeout),
+            request_timeout: Duration::from_secs(self.request_timeout),
+            proxy: self.proxy.clone(),
+            cache: Some(CacheConfig {
+                ttl: Duration::from_secs(self.cache_ttl * 3600),
+                force_refresh: self.refresh,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn mp3_dir(&self) -> String {
+        self.mp3_dir
+            .clone()
+            .unwrap_or_else(|| [DATA_DIR, &self.dictionary(), MP3_DIR].join("/"))
+    }
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use std::time::Duration;
+
+    use clap::{CommandFactory, Parser};
+
+    use common_libs::scoring::Algorithm;
+
+    use super::{Cli, Command, Direction, ExportFormat, InputMode};
+
+    #[test]
+    fn definition_test() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn quiz_test() {
+        let cli = Cli::try_parse_from(["matkat", "-d", "Oxford", "-s", "70", "quiz", "-h", "true"]).unwrap();
+        assert_eq!("oxford", cli.dictionary());
+        assert_eq!(70, cli.passing_score);
+        assert_eq!(20, cli.maximum_questions);
+        assert_eq!(Algorithm::Damerau, cli.algorithm);
+        assert_eq!("data/en-en/oxford/json", cli.json_dir());
+        match cli.command {
+            Command::Quiz(quiz) => assert!(quiz.hide_word && !quiz.use_definition && quiz.translate.is_none()),
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["matkat", "quiz", "--translate", "bg-en"]).unwrap();
+        match cli.command {
+            Command::Quiz(quiz) => assert_eq!(Some(Direction::BgEn), quiz.translate),
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert_eq!("en-bg:call off", Direction::EnBg.statistic_key("Call off"));
+        assert!(Cli::try_parse_from(["matkat", "quiz", "-r", "bg-de"]).is_err());
+        assert!(Cli::try_parse_from(["matkat"]).is_err());
+    }
+
+    #[test]
+    fn download_test() {
+        let cli = Cli::try_parse_from(["matkat", "-m", "mp3", "download", "-i", "file", "-d", "collins"]).unwrap();
+        assert_eq!("collins", cli.dictionary());
+        assert_eq!("mp3", cli.mp3_dir());
+        assert_eq!("data/en-en/collins/json", cli.json_dir());
+        match cli.command {
+            Command::Download(download) => {
+                assert_eq!(InputMode::File, download.mode);
+                assert_eq!("download/tmp", download.html_tmp_folder);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn download_all_test() {
+        let cli = Cli::try_parse_from(["matkat", "-j", "json", "download", "-d", "All"]).unwrap();
+        assert_eq!("all", cli.dictionary());
+        assert_eq!("json/oxford", cli.json_dir_for("oxford"));
+        assert!(Cli::try_parse_from(["matkat", "-d", "all", "quiz"]).is_err());
+    }
+
+    #[test]
+    fn listen_test() {
+        assert!(matches!(Cli::try_parse_from(["matkat", "listen"]).unwrap().command, Command::Listen(_)));
+        assert!(Cli::try_parse_from(["matkat", "-d", "webster", "listen"]).is_err());
+        assert!(Cli::try_parse_from(["matkat", "-s", "101", "listen"]).is_err());
+    }
+
+    #[test]
+    fn export_test() {
+        let cli = Cli::try_parse_from(["matkat", "export", "--format", "tsv", "-o", "deck.tsv"]).unwrap();
+        match cli.command {
+            Command::Export(export) => {
+                assert_eq!(ExportFormat::Tsv, export.format);
+                assert_eq!(Some("deck.tsv".to_string()), export.output);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ingest_test() {
+        let cli = Cli::try_parse_from(["matkat", "-f", "article.txt", "ingest", "-o", "new.txt"]).unwrap();
+        match cli.command {
+            Command::Ingest(ingest) => assert_eq!((Some("new.txt".to_string()), None), (ingest.output, ingest.known)),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn algorithm_test() {
+        let cli = Cli::try_parse_from(["matkat", "-a", "token-set", "listen"]).unwrap();
+        assert_eq!(Algorithm::TokenSet, cli.algorithm);
+        assert!(Cli::try_parse_from(["matkat", "-a", "soundex", "listen"]).is_err());
+    }
+
+    #[test]
+    fn http_client_test() {
+        let config = Cli::try_parse_from(["matkat", "download"]).unwrap().http_client_config();
+        assert_eq!(8, config.pool_max_idle_per_host);
+        assert_eq!(Duration::from_secs(30), config.request_timeout);
+        assert!(config.proxy.is_none());
+        let cache = config.cache.unwrap();
+        assert_eq!(Duration::from_secs(7 * 24 * 60 * 60), cache.ttl);
+        assert!(!cache.force_refresh);
+
+        let cli = Cli::try_parse_from([
+            "matkat",
+            "--pool-size",
+            "2",
+            "--connect-timeout",
+            "5",
+            "--request-timeout",
+            "60",
+            "--proxy",
+            "http://127.0.0.1:3128",
+            "--cache-ttl",
+            "1",
+            "--refresh",
+            "download",
+        ])
+        .unwrap();
+        let config = cli.http_client_config();
+        assert_eq!(2, config.pool_max_idle_per_host);
+        assert_eq!(Duration::from_secs(5), config.connect_timeout);
+        assert_eq!(Duration::from_secs(60), config.request_timeout);
+        assert_eq!(Some("http://127.0.0.1:3128".to_string()), config.proxy);
+        let cache = config.cache.unwrap();
+        assert_eq!(Duration::from_secs(3600), cache.ttl);
+        assert!(cache.force_refresh);
+    }
+}
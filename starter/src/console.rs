@@ -0,0 +1,50 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Given { text: String, respond_time: Duration },
+    Timeout,
+    /// The input was closed.
+    Closed,
+}
+
+/// Reads the terminal on its own thread so questions can time out.
+pub struct Console {
+    lines: Receiver<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Console { lines }
+    }
+
+    /// Prints the prompt and waits for one line. Whatever was typed before
+    /// the prompt, e.g. while a word was playing, is dropped.
+    pub fn ask(&self, prompt: &str, timeout: Duration) -> Answer {
+        while self.lines.try_recv().is_ok() {}
+        print!("{} ", prompt);
+        let _ = io::stdout().flush();
+        let started = Instant::now();
+        match self.lines.recv_timeout(timeout) {
+            Ok(text) => Answer::Given {
+                text: text.trim().to_string(),
+                respond_time: started.elapsed(),
+            },
+            Err(RecvTimeoutError::Timeout) => Answer::Timeout,
+            Err(RecvTimeoutError::Disconnected) => Answer::Closed,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{fs, path::Path};
+
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::save,
+};
+use log::error;
+use mk_scraper::{
+    dictionary_scraper::set_tmp_dir, http_client::default_fetcher, merger::MERGED_DICTIONARIES,
+    scrape_report::ScrapeReport, scrape_word,
+};
+
+use crate::{
+    cli::{Cli, DownloadArgs, InputMode, ALL_DICTIONARIES},
+    words::{entry_file, read_manual, read_words},
+};
+
+/// Scrapes the words missing from the JSON directory of every chosen
+/// dictionary and saves them there, one `<word>.json` each.
+pub async fn run(cli: &Cli, args: &DownloadArgs) -> ServiceExuctionResult<()> {
+    let words = match args.mode {
+        InputMode::File if cli.file.is_empty() => {
+            return Err(ServiceError {
+                message: "file.is.required: --file <FILE>".to_string(),
+                error_type: ServiceErrorType::Failure,
+            })
+        }
+        InputMode::File => read_words(&cli.file)?,
+        InputMode::Manual => read_manual(),
+    };
+    set_tmp_dir(&args.html_tmp_folder);
+
+    let dictionaries = match cli.dictionary().as_str() {
+        ALL_DICTIONARIES => MERGED_DICTIONARIES.iter().map(|name| name.to_string()).collect(),
+        dictionary => vec![dictionary.to_string()],
+    };
+    for dictionary in dictionaries {
+        let json_dir = cli.json_dir_for(&dictionary);
+        fs::create_dir_all(&json_dir)?;
+        let (report, skipped) = download(&dictionary, &json_dir, &words).await;
+        print_summary(&dictionary, &report, skipped);
+    }
+    Ok(())
+}
+
+/// Returns the report of the scraped words and how many were already there.
+async fn download(dictionary: &str, json_dir: &str, words: &[String]) -> (ScrapeReport, usize) {
+    let (existing, missing): (Vec<&String>, Vec<&String>) = words
+        .iter()
+        .partition(|word| Path::new(&entry_file(json_dir, word)).exists());
+
+    let mut report = ScrapeReport::new(missing.iter().map(|word| word.to_string()).collect());
+    for (done, word) in missing.iter().enumerate() {
+        let result = scrape_word(default_fetcher(), dictionary, word).await;
+        match &result {
+            Ok(entry) => {
+                if let Err(err) = save(&entry_file(json_dir, word), entry) {
+                    error!("failed.to.save: {} -> {}", word, err.message);
+                }
+                println!("[{}/{}] {}: {} definitions", done + 1, missing.len(), word, entry.definitions.len());
+            }
+            Err(err) => println!("[{}/{}] {}: {}", done + 1, missing.len(), word, err.message),
+        }
+        report.add(dictionary, word, result);
+    }
+    (report, existing.len())
+}
+
+fn print_summary(dictionary: &str, report: &ScrapeReport, skipped: usize) {
+    println!("\n{}: {} already downloaded; {}", dictionary, skipped, report.summary());
+    if let Some(not_found) = report.not_found.get(dictionary) {
+        println!("not found: {}", not_found.join(", "));
+    }
+    for (word, reason) in report.errors.get(dictionary).into_iter().flatten() {
+        println!("failed: {} -> {}", word, reason);
+    }
+}
@@ -0,0 +1,42 @@
+use common_libs::{error::ServiceExuctionResult, files::file_name};
+use mk_scraper::{
+    export::{export_apkg, export_delimited, load_cards, EXPORT_DIR},
+    glossary::{Glossary, TRANSLATIONS_FILE},
+};
+
+use crate::{
+    cli::{Cli, ExportArgs, ExportFormat},
+    words::read_words,
+};
+
+/// Saves the downloaded words of the dictionary, or the `--file` ones, as a deck.
+pub fn run(cli: &Cli, args: &ExportArgs) -> ServiceExuctionResult<()> {
+    let words = if cli.file.is_empty() { vec![] } else { read_words(&cli.file)? };
+    let glossary = Glossary::load(TRANSLATIONS_FILE)?;
+    let cards = load_cards(&cli.json_dir(), &cli.mp3_dir(), &words, &glossary)?;
+
+    let dictionary = cli.dictionary();
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| file_name(EXPORT_DIR, dictionary.as_str(), args.format.ext()));
+    match args.format {
+        ExportFormat::Apkg => {
+            let deck = args.deck.clone().unwrap_or_else(|| format!("matkat::{}", dictionary));
+            export_apkg(&cards, &output, &deck)?
+        }
+        ExportFormat::Csv => export_delimited(&cards, &output, b',')?,
+        ExportFormat::Tsv => export_delimited(&cards, &output, b'\t')?,
+    }
+
+    println!("{} cards -> {}", cards.len(), output);
+    let without_audio: Vec<&str> = cards
+        .iter()
+        .filter(|card| card.audio.is_none())
+        .map(|card| card.word.as_str())
+        .collect();
+    if !without_audio.is_empty() {
+        println!("without audio: {}", without_audio.join(", "));
+    }
+    Ok(())
+}
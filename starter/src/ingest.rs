@@ -0,0 +1,50 @@
+use std::fs;
+
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    ingest::ingest,
+};
+use log::info;
+use mk_scraper::glossary::{Glossary, TRANSLATIONS_FILE};
+
+use crate::{
+    cli::{Cli, IngestArgs},
+    words::{load_entries, read_words},
+};
+
+/// Reads the text of `--file` and lists its words that are neither
+/// downloaded, in the glossary nor in `--known`. Their multi-word ones are
+/// looked for in the text too.
+pub fn run(cli: &Cli, args: &IngestArgs) -> ServiceExuctionResult<()> {
+    if cli.file.is_empty() {
+        return Err(ServiceError {
+            message: "file.is.required: --file <FILE>".to_string(),
+            error_type: ServiceErrorType::Failure,
+        });
+    }
+    let text = fs::read_to_string(&cli.file)?;
+
+    let mut known: Vec<String> = match load_entries(&cli.json_dir(), &[]) {
+        Ok(entries) => entries.into_iter().map(|entry| entry.word).collect(),
+        Err(err) => {
+            info!("no.downloaded.words: {}", err.message);
+            vec![]
+        }
+    };
+    known.extend(Glossary::load(TRANSLATIONS_FILE)?.translations.into_iter().map(|translation| translation.key));
+    if let Some(file) = &args.known {
+        known.extend(read_words(file)?);
+    }
+    let expressions: Vec<String> = known.iter().filter(|word| word.trim().contains(' ')).cloned().collect();
+    let words = ingest(&text, &known, &expressions);
+
+    match &args.output {
+        Some(output) => {
+            fs::write(output, words.iter().map(|word| format!("{}\n", word)).collect::<String>())?;
+            println!("{} new words -> {}", words.len(), output);
+            println!("download them with: matkat -f {} download -i file", output);
+        }
+        None => words.iter().for_each(|word| println!("{}", word)),
+    }
+    Ok(())
+}
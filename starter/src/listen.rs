@@ -0,0 +1,73 @@
+use std::{path::Path, time::Duration};
+
+use common_libs::{
+    error::ServiceExuctionResult,
+    files::file_name,
+    player::play,
+    scoring::{best_score_with, score_with},
+    stats::Outcome,
+};
+use mk_scraper::{constants::MP3_EXT, model::DictionaryEntry};
+
+use crate::{
+    cli::{Cli, ListenArgs},
+    console::Console,
+    session,
+    words::{load_entries, read_words},
+};
+
+/// Dictation: plays the downloaded pronunciations, scores what was typed
+/// and records every answer in the statistic.
+pub fn run(cli: &Cli, args: &ListenArgs) -> ServiceExuctionResult<()> {
+    let words = if cli.file.is_empty() { vec![] } else { read_words(&cli.file)? };
+    let entries = session::plan(load_entries(&cli.json_dir(), &words)?, |entry| entry.word.as_str())?;
+    let console = Console::new();
+    let timeout = Duration::from_secs(cli.timeout);
+
+    let mut asked = 0;
+    let mut passed = 0;
+    for (entry, file) in entries
+        .iter()
+        .filter_map(|entry| audio_file(cli, entry).map(|file| (entry, file)))
+        .take(cli.maximum_questions)
+    {
+        println!("\n#{}", asked + 1);
+        play(&file, cli.iterations)?;
+        let prompt = if args.use_definition { "What does it mean?" } else { "What did you hear?" };
+        let answer = console.ask(prompt, timeout);
+        let (outcome, points, respond_time) = match session::grade(cli, answer, |text| {
+            if args.use_definition {
+                best_score_with(cli.algorithm, &entry.definitions, text)
+            } else {
+                score_with(cli.algorithm, &entry.word, text)
+            }
+        }) {
+            Some(graded) => graded,
+            None => break,
+        };
+        asked += 1;
+
+        match outcome {
+            Outcome::Success => {
+                passed += 1;
+                println!("Correct! ({}%) {}", points, entry.word);
+            }
+            Outcome::Error => println!("Wrong ({}%), it is: {}", points, entry.word),
+            Outcome::Timeout => println!("Time is up, it is: {}", entry.word),
+        }
+        session::record(&entry.word, outcome, points, respond_time);
+    }
+    session::print_progress(passed, asked);
+    Ok(())
+}
+
+/// The downloaded pronunciation: the entry's `file`, or `<mp3-dir>/<word>.mp3`.
+pub fn audio_file(cli: &Cli, entry: &DictionaryEntry) -> Option<String> {
+    let word = entry.word.trim().to_lowercase();
+    entry
+        .file
+        .clone()
+        .into_iter()
+        .chain([file_name(cli.mp3_dir().as_str(), word.as_str(), MP3_EXT)])
+        .find(|file| Path::new(file).exists())
+}
@@ -1,9 +1,49 @@
-use common_libs;
-use log::info;
+mod cli;
+mod console;
+mod download;
+mod export;
+mod ingest;
+mod listen;
+mod quiz;
+mod session;
+mod words;
 
+use clap::Parser;
+use cli::{Cli, Command};
+use log::{error, info};
+use mk_scraper::{
+    http_client, registry,
+    scraper_config::SCRAPERS_CONFIG,
+    wiktionary::{self, WIKTIONARY_DUMP},
+};
 
-fn main() {
+#[tokio::main]
+async fn main() {
     common_libs::configure_log4rs();
-    info!("you did it.");
-    
+    if let Err(err) = registry::load_config(SCRAPERS_CONFIG) {
+        println!("invalid.scrapers.config: {}", err.message);
+        std::process::exit(1);
+    }
+    if let Err(err) = wiktionary::register(WIKTIONARY_DUMP) {
+        error!("invalid.wiktionary.dump: {}", err.message);
+    }
+
+    let cli = Cli::parse();
+    info!("{:?}", cli);
+    if let Err(err) = http_client::init(cli.http_client_config()) {
+        println!("invalid.http.client.config: {}", err.message);
+        std::process::exit(1);
+    }
+    let result = match &cli.command {
+        Command::Download(args) => download::run(&cli, args).await,
+        Command::Listen(args) => listen::run(&cli, args),
+        Command::Quiz(args) => quiz::run(&cli, args),
+        Command::Stats => session::run(),
+        Command::Export(args) => export::run(&cli, args),
+        Command::Ingest(args) => ingest::run(&cli, args),
+    };
+    if let Err(err) = result {
+        println!("{}", err.message);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use common_libs::{
+    error::ServiceExuctionResult,
+    scoring::{self, best_score_with, score_with},
+    stats::Outcome,
+    utils::shuffle,
+};
+
+use log::warn;
+use mk_scraper::glossary::{Glossary, Translation, TRANSLATIONS_FILE};
+
+use crate::{
+    cli::{Cli, Direction, QuizArgs},
+    console::Console,
+    session,
+    words::{load_entries, read_words},
+};
+
+const MASK: &str = "___";
+
+/// Shows the shuffled definitions of a word and asks for the word; with
+/// `--use-definition` shows the word and asks what it means.
+pub fn run(cli: &Cli, args: &QuizArgs) -> ServiceExuctionResult<()> {
+    if let Some(direction) = args.translate {
+        return translate(cli, &Glossary::load(TRANSLATIONS_FILE)?, direction);
+    }
+    let glossary = Glossary::load(TRANSLATIONS_FILE).unwrap_or_else(|err| {
+        warn!("translations.not.loaded: {}", err.message);
+        Glossary::default()
+    });
+    let words = if cli.file.is_empty() { vec![] } else { read_words(&cli.file)? };
+    let entries = load_entries(&cli.json_dir(), &words)?;
+    let entries: Vec<_> = session::plan(glossary.join(entries), |(entry, _)| entry.word.as_str())?
+        .into_iter()
+        .filter(|(entry, _)| !entry.definitions.is_empty())
+        .take(cli.maximum_questions)
+        .collect();
+    let console = Console::new();
+    let timeout = Duration::from_secs(cli.timeout);
+
+    let mut asked = 0;
+    let mut passed = 0;
+    for (entry, translation) in &entries {
+        println!("\n#{} of {}", asked + 1, entries.len());
+        let answer = if args.use_definition {
+            println!("  {}", entry.word);
+            console.ask("What does it mean?", timeout)
+        } else {
+            for definition in shuffle(Box::new(entry.definitions.clone())) {
+                let definition = if args.hide_word { mask(&definition, &entry.word) } else { definition };
+                println!("  - {}", definition);
+            }
+            console.ask("Which word is it?", timeout)
+        };
+        let (outcome, points, respond_time) = match session::grade(cli, answer, |text| {
+            if args.use_definition {
+                best_score_with(cli.algorithm, &entry.definitions, text)
+            } else {
+                score_with(cli.algorithm, &entry.word, text)
+            }
+        }) {
+            Some(graded) => graded,
+            None => break,
+        };
+        asked += 1;
+
+        match outcome {
+            Outcome::Success => {
+                passed += 1;
+                println!("Correct! ({}%) {}", points, entry.word);
+            }
+            Outcome::Error if args.use_definition => {
+                println!("Wrong ({}%), it means:", points);
+                for definition in &entry.definitions {
+                    println!("  - {}", definition);
+                }
+            }
+            Outcome::Error => println!("Wrong ({}%), it is: {}", points, entry.word),
+            Outcome::Timeout => println!("Time is up, it is: {}", entry.word),
+        }
+        if let Some(translation) = translation {
+            println!("  bg: {}", translation.value);
+        }
+        session::record(&entry.word, outcome, points, respond_time);
+    }
+    session::print_progress(passed, asked);
+    Ok(())
+}
+
+/// Asks for the English word of the Bulgarian translation or the other way
+/// round. The words are the `--file` ones, or the whole glossary.
+fn translate(cli: &Cli, glossary: &Glossary, direction: Direction) -> ServiceExuctionResult<()> {
+    let words = if cli.file.is_empty() { vec![] } else { read_words(&cli.file)? };
+    let untranslated = glossary.untranslated();
+    if !untranslated.is_empty() {
+        println!("without translation: {}", untranslated.join(", "));
+    }
+    let unknown: Vec<&str> = words
+        .iter()
+        .filter(|word| glossary.get(word).is_none())
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        println!("not in the glossary: {}", unknown.join(", "));
+    }
+
+    let translations: Vec<(String, Translation)> = glossary
+        .translations
+        .iter()
+        .filter(|translation| !translation.is_empty())
+        .filter(|translation| words.is_empty() || words.contains(&translation.key.trim().to_lowercase()))
+        .map(|translation| (direction.statistic_key(&translation.key), translation.clone()))
+        .collect();
+    let translations: Vec<_> = session::plan(translations, |(key, _)| key.as_str())?
+        .into_iter()
+        .take(cli.maximum_questions)
+        .collect();
+    let console = Console::new();
+    let timeout = Duration::from_secs(cli.timeout);
+
+    let mut asked = 0;
+    let mut passed = 0;
+    for (key, translation) in &translations {
+        println!("\n#{} of {}", asked + 1, translations.len());
+        let (shown, expected) = match direction {
+            Direction::BgEn => (translation.value.as_str(), translation.key.as_str()),
+            Direction::EnBg => (translation.key.as_str(), translation.value.as_str()),
+        };
+        println!("  {}", shown);
+        let prompt = match direction {
+            Direction::BgEn => "In English?",
+            Direction::EnBg => "На български?",
+        };
+        let answer = console.ask(prompt, timeout);
+        let (outcome, points, respond_time) = match session::grade(cli, answer, |text| match direction {
+            Direction::BgEn => score_with(cli.algorithm, &translation.key, text),
+            Direction::EnBg => best_score_with(cli.algorithm, &translation.answers(), text),
+        }) {
+            Some(graded) => graded,
+            None => break,
+        };
+        asked += 1;
+
+        match outcome {
+            Outcome::Success => {
+                passed += 1;
+                println!("Correct! ({}%) {}", points, expected);
+            }
+            Outcome::Error => println!("Wrong ({}%), it is: {}", points, expected),
+            Outcome::Timeout => println!("Time is up, it is: {}", expected),
+        }
+        if !translation.description.is_empty() {
+            println!("  {}", translation.description);
+        }
+        session::record(key, outcome, points, respond_time);
+    }
+    session::print_progress(passed, asked);
+    Ok(())
+}
+
+/// Replaces the word in the definition, including forms like `deferred` or
+/// `stifling`. A phrase is only replaced as a whole.
+pub fn mask(definition: &str, word: &str) -> String {
+    let word = word.trim().to_lowercase();
+    if word.contains(' ') {
+        return replace_ignore_case(definition, &word);
+    }
+    let stem = scoring::stem(&word);
+    definition
+        .split(' ')
+        .map(|token| {
+            let start = token.find(char::is_alphanumeric).unwrap_or(token.len());
+            let end = token
+                .char_indices()
+                .rev()
+                .find(|(_, c)| c.is_alphanumeric())
+                .map_or(start, |(i, c)| i + c.len_utf8());
+            if start < end && scoring::stem(&token[start..end].to_lowercase()) == stem {
+                format!("{}{}{}", &token[..start], MASK, &token[end..])
+            } else {
+                token.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Masks every whole-word occurrence of the lowercase `phrase`; compared
+/// char by char, so a letter whose lowercase has another length still matches.
+fn replace_ignore_case(text: &str, phrase: &str) -> String {
+    let mut masked = String::new();
+    let mut last = 0;
+    let mut previous: Option<char> = None;
+    for (start, c) in text.char_indices() {
+        if start >= last && !previous.is_some_and(char::is_alphanumeric) {
+            if let Some(end) = match_len(&text[start..], phrase).map(|len| start + len) {
+                if !text[end..].starts_with(char::is_alphanumeric) {
+                    masked.push_str(&text[last..start]);
+                    masked.push_str(MASK);
+                    last = end;
+                }
+            }
+        }
+        previous = Some(c);
+    }
+    masked.push_str(&text[last..]);
+    masked
+}
+
+/// The bytes of `text` that make up `phrase` at its start, ignoring case.
+fn match_len(text: &str, phrase: &str) -> Option<usize> {
+    let mut expected = phrase.chars();
+    for (index, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if expected.next() != Some(lower) {
+                return None;
+            }
+        }
+        if expected.as_str().is_empty() {
+            return Some(index + c.len_utf8());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod quiz_tests {
+    use super::mask;
+
+    #[test]
+    fn mask_test() {
+        assert_eq!(
+            "if you ___ something, you ___ it",
+            mask("if you defer something, you Deferred it", "defer")
+        );
+        assert_eq!("(___) to stop", mask("(Stifling) to stop", "stifle"));
+        assert_eq!("to make something ___.", mask("to make something stance.", "stance"));
+        assert_eq!("if you ___, you end", mask("if you wind up, you end", "wind up"));
+        assert_eq!("the wind is up", mask("the wind is up", "wind up"));
+        assert_eq!("rewind upward", mask("rewind upward", "wind up"));
+        assert_eq!("___, then ___.", mask("Wind up, then WIND UP.", "wind up"));
+        assert_eq!("İstanbul ___ here", mask("İstanbul wind up here", "wind up"));
+        assert_eq!("nothing here", mask("nothing here", "outlier"));
+        assert_eq!("a small ___, a bar", mask("a small café, a bar", "café"));
+        assert_eq!("her ___", mask("her fiancé", "fiancé"));
+        assert_eq!("to ___ or actually do", mask("to act or actually do", "act"));
+        assert_eq!("they ___, he is ___", mask("they deferred, he is deferring", "defer"));
+        assert_eq!("the ___ are new", mask("the news are new", "news"));
+    }
+}
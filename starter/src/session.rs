@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use common_libs::{
+    error::ServiceExuctionResult,
+    stats::{Outcome, Statistic, STATISTIC_FILE},
+    utils::shuffle,
+};
+use log::error;
+
+use crate::{cli::Cli, console::Answer};
+
+/// How many of the hardest words the progress report lists.
+const HARDEST_WORDS: usize = 5;
+
+/// The items in the order they are asked: words due for review first,
+/// then new words in random order, then the rest by review date.
+pub fn plan<T: Clone>(items: Vec<T>, word: fn(&T) -> &str) -> ServiceExuctionResult<Vec<T>> {
+    let statistic = Statistic::load(STATISTIC_FILE)?;
+    let now = Utc::now();
+    println!("{} words due for review", statistic.due(now).len());
+    Ok(order(&statistic, items, word, now))
+}
+
+fn order<T: Clone>(statistic: &Statistic, items: Vec<T>, word: fn(&T) -> &str, now: DateTime<Utc>) -> Vec<T> {
+    let mut items = shuffle(Box::new(items));
+    items.sort_by_key(|item| statistic.priority(word(item), now));
+    items
+}
+
+/// Outcome, score and response time of the answer; None when the input was
+/// closed. A timeout scores 0 and takes the whole `--timeout`.
+pub fn grade<F: Fn(&str) -> u8>(cli: &Cli, answer: Answer, score: F) -> Option<(Outcome, u8, u64)> {
+    match answer {
+        Answer::Given { text, respond_time } => {
+            let points = score(&text);
+            let outcome = if points >= cli.passing_score { Outcome::Success } else { Outcome::Error };
+            Some((outcome, points, respond_time.as_secs()))
+        }
+        Answer::Timeout => {
+            println!();
+            Some((Outcome::Timeout, 0, cli.timeout))
+        }
+        Answer::Closed => None,
+    }
+}
+
+/// Adds the answer to the statistic file right away, so a session that is
+/// cut short keeps its answers.
+pub fn record(word: &str, outcome: Outcome, score: u8, respond_time: u64) {
+    if let Err(err) = Statistic::update(STATISTIC_FILE, |statistic| {
+        statistic.record(word, outcome, score, respond_time)
+    }) {
+        error!("statistic.not.saved: {}", err.message);
+    }
+}
+
+/// Prints the session result followed by the progress over all sessions.
+pub fn print_progress(passed: usize, asked: usize) {
+    println!("\n{} of {} passed", passed, asked);
+    match Statistic::load(STATISTIC_FILE) {
+        Ok(statistic) => println!("\n{}", statistic.report(HARDEST_WORDS)),
+        Err(err) => error!("statistic.not.loaded: {}", err.message),
+    }
+}
+
+/// The `stats` subcommand.
+pub fn run() -> ServiceExuctionResult<()> {
+    println!("{}", Statistic::load(STATISTIC_FILE)?.report(HARDEST_WORDS));
+    Ok(())
+}
+
+#[cfg(test)]
+mod session_tests {
+    use chrono::{Duration, Utc};
+    use clap::Parser;
+    use common_libs::stats::{Outcome, Statistic, WordStatistic};
+    use mk_scraper::model::{Dictionary, DictionaryEntry};
+
+    use super::{grade, order};
+    use crate::{cli::Cli, console::Answer};
+
+    #[test]
+    fn order_test() {
+        let now = Utc::now();
+        let mut statistic = Statistic::default();
+        statistic.record("defer", Outcome::Success, 100, 2);
+        statistic.record("stance", Outcome::Error, 10, 20);
+        statistic.records[1].next_review = Some(now - Duration::days(1));
+        statistic.record("anger", Outcome::Timeout, 0, 30);
+        statistic.records[2].next_review = Some(now - Duration::days(3));
+
+        let entries = ["defer", "bolster", "stance", "anger", "outlier"]
+            .map(|word| DictionaryEntry::new(Dictionary::Cambridge, word))
+            .to_vec();
+        let words: Vec<String> = order(&statistic, entries, |entry| entry.word.as_str(), now)
+            .into_iter()
+            .map(|entry| entry.word)
+            .collect();
+        assert_eq!(["anger", "stance"], words[..2]);
+        assert!(words[2..4].contains(&"bolster".to_string()) && words[2..4].contains(&"outlier".to_string()));
+        assert_eq!("defer", words[4]);
+    }
+
+    #[test]
+    fn grade_test() {
+        let cli = Cli::try_parse_from(["matkat", "quiz"]).unwrap();
+        assert_eq!(85, cli.passing_score);
+        let answer = |text: &str| Answer::Given {
+            text: text.to_string(),
+            respond_time: std::time::Duration::from_secs(3),
+        };
+
+        assert_eq!(Some((Outcome::Success, 85, 3)), grade(&cli, answer("defer"), |_| 85));
+        let failed = grade(&cli, answer("defe"), |_| 80);
+        assert_eq!(Some((Outcome::Error, 80, 3)), failed);
+        assert_eq!(Some((Outcome::Timeout, 0, 30)), grade(&cli, Answer::Timeout, |_| 100));
+        assert_eq!(None, grade(&cli, Answer::Closed, |_| 100));
+
+        let mut record = WordStatistic::new("defer");
+        record.add(Outcome::Success, 100, 2);
+        record.add(Outcome::Success, 100, 2);
+        let (outcome, score, respond_time) = failed.unwrap();
+        record.add(outcome, score, respond_time);
+        assert_eq!((1, 0), (record.interval, record.repetitions));
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::Path,
+};
+
+use common_libs::{
+    error::{ServiceError, ServiceErrorType, ServiceExuctionResult},
+    files::{file_name, parse_file},
+};
+use log::error;
+use mk_scraper::{constants::JSON_EXT, model::DictionaryEntry};
+
+/// Words of a list file, one per line (or separated by punctuation), lower
+/// case and without duplicates.
+pub fn read_words(file: &str) -> ServiceExuctionResult<Vec<String>> {
+    Ok(unique(parse_file(file)?))
+}
+
+/// Words typed in the terminal until an empty line.
+pub fn read_manual() -> Vec<String> {
+    println!("Type the words, one per line. An empty line ends the list.");
+    let mut words = vec![];
+    for line in io::stdin().lock().lines() {
+        match line {
+            Ok(line) if !line.trim().is_empty() => words.push(line),
+            _ => break,
+        }
+    }
+    unique(words)
+}
+
+fn unique(words: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = vec![];
+    for word in words {
+        let word = word.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        if !word.is_empty() && !unique.contains(&word) {
+            unique.push(word);
+        }
+    }
+    unique
+}
+
+pub fn entry_file(json_dir: &str, word: &str) -> String {
+    file_name(json_dir, word, JSON_EXT)
+}
+
+pub fn read_entry(file: &str) -> ServiceExuctionResult<DictionaryEntry> {
+    let content = fs::read_to_string(file)?;
+    serde_json::from_str(&content).map_err(|err| ServiceError {
+        message: format!("invalid.json: {} -> {}", file, err),
+        error_type: ServiceErrorType::SerializationError,
+    })
+}
+
+/// Stored entries of the words, or of every word in `json_dir` when no words
+/// are given. Missing and broken files are logged and skipped.
+pub fn load_entries(json_dir: &str, words: &[String]) -> ServiceExuctionResult<Vec<DictionaryEntry>> {
+    let files = if words.is_empty() {
+        if !Path::new(json_dir).is_dir() {
+            return Err(ServiceError {
+                message: format!("dir.not.found: {}", json_dir),
+                error_type: ServiceErrorType::ResourceNotFound,
+            });
+        }
+        let mut files = vec![];
+        for entry in fs::read_dir(json_dir)? {
+            let path = entry?.path().to_string_lossy().to_string();
+            if path.ends_with(JSON_EXT) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        files
+    } else {
+        words.iter().map(|word| entry_file(json_dir, word)).collect()
+    };
+
+    let mut entries = vec![];
+    for file in files {
+        match read_entry(&file) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => error!("entry.skipped: {} -> {}", file, err.message),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod words_tests {
+    use std::fs;
+
+    use common_libs::files::{save, temp_dir};
+    use mk_scraper::model::{Dictionary, DictionaryEntry};
+
+    use super::{entry_file, load_entries, read_words};
+
+    #[test]
+    fn read_words_test() {
+        let file = format!("{}/words.txt", temp_dir("starter_words_tests"));
+        fs::write(&file, "Sentinel\ndefer\n\nwind  up\ndefer\n").unwrap();
+        assert_eq!(vec!["sentinel", "defer", "wind up"], read_words(&file).unwrap());
+        assert!(read_words("missing.txt").is_err());
+    }
+
+    #[test]
+    fn load_entries_test() {
+        let json_dir = temp_dir("starter_entries_tests");
+        let entry = DictionaryEntry {
+            definitions: vec!["to delay something until a later time".to_string()],
+            ..DictionaryEntry::new(Dictionary::Cambridge, "defer")
+        };
+        save(&entry_file(&json_dir, "defer"), &entry).unwrap();
+        fs::write(entry_file(&json_dir, "broken"), "{").unwrap();
+
+        assert_eq!(1, load_entries(&json_dir, &[]).unwrap().len());
+        let entries = load_entries(&json_dir, &["defer".to_string(), "missing".to_string()]).unwrap();
+        assert_eq!("defer", entries[0].word);
+        assert_eq!(1, entries.len());
+        assert!(load_entries("missing/dir", &[]).is_err());
+    }
+}